@@ -0,0 +1,416 @@
+// In-game console for tweaking the camera by typing commands, toggled with the backtick key.
+//
+// Supported commands:
+//   help                      list the commands and settings
+//   clear                     clear the console buffer
+//   set <setting> <value>     change a FreeCamera setting, e.g. `set sensitivity 0.3`
+//   mode <fly|walk>           switch the movement mode
+//   basis <camera|yaw|world>  choose what the movement keys are relative to
+//   teleport <x> <y> <z>      move the camera and stop it
+//   snap <degrees>            turn to the nearest multiple of the given yaw angle
+//   cursor <grabbed|confined|free>  choose what happens to the cursor while it is grabbed
+//   ease <linear|smoothstep|in_out|out_cubic>  choose the curve used to ease snap turns
+
+use bevy::{
+    input::{
+        ButtonState, InputSystems,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+
+use villa_bevy::easing::EaseKind;
+use villa_bevy::free_camera::{
+    CursorMode, FreeCamera, FreeCameraState, MovementBasis, MovementMode, buffer_movement_input,
+};
+
+// Number of output lines kept in the console buffer.
+const CONSOLE_HISTORY: usize = 12;
+
+// Accessor for one of the `f32` fields of `FreeCamera`.
+type SettingField = fn(&mut FreeCamera) -> &mut f32;
+
+// Settings the `set` command can change, by name.
+const SETTINGS: &[(&str, SettingField)] = &[
+    ("sensitivity", |free_camera| &mut free_camera.sensitivity),
+    ("friction", |free_camera| &mut free_camera.friction),
+    ("scroll_factor", |free_camera| {
+        &mut free_camera.scroll_factor
+    }),
+    ("walk_speed", |free_camera| &mut free_camera.walk_speed),
+    ("run_speed", |free_camera| &mut free_camera.run_speed),
+    ("max_look_delta", |free_camera| {
+        &mut free_camera.max_look_delta
+    }),
+    ("step_height", |free_camera| &mut free_camera.step_height),
+    ("gravity", |free_camera| &mut free_camera.gravity),
+    ("jump_speed", |free_camera| &mut free_camera.jump_speed),
+    ("terminal_velocity", |free_camera| {
+        &mut free_camera.terminal_velocity
+    }),
+    ("air_control", |free_camera| &mut free_camera.air_control),
+    ("input_buffer_ms", |free_camera| {
+        &mut free_camera.input_buffer_ms
+    }),
+    ("slide_smoothing", |free_camera| {
+        &mut free_camera.slide_smoothing
+    }),
+    ("pitch_speed_falloff", |free_camera| {
+        &mut free_camera.pitch_speed_falloff
+    }),
+];
+
+// Plugin that adds the console UI and runs the typed commands.
+pub struct ConsolePlugin;
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_systems(Startup, spawn_console)
+            // Runs right after input is collected so it can hide keystrokes from everything else,
+            // including the controller's input buffer
+            .add_systems(
+                PreUpdate,
+                read_console_input
+                    .after(InputSystems)
+                    .before(buffer_movement_input),
+            )
+            .add_systems(Update, (run_console_commands, update_console_text).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    input: String,
+    lines: Vec<String>,
+    // Lines entered with Enter that still have to be run.
+    submitted: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+        let excess = self.lines.len().saturating_sub(CONSOLE_HISTORY);
+        self.lines.drain(..excess);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ConsoleCommand {
+    Help,
+    Clear,
+    Set(String, f32),
+    Mode(MovementMode),
+    Basis(MovementBasis),
+    Teleport(Vec3),
+    Snap(f32),
+    Cursor(CursorMode),
+    Ease(EaseKind),
+}
+
+fn parse_command(line: &str) -> Result<ConsoleCommand, String> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return Err("empty command".to_string());
+    };
+    let args: Vec<&str> = words.collect();
+
+    let number = |arg: &str| {
+        arg.parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| format!("`{arg}` is not a number"))
+    };
+
+    match (name, args.as_slice()) {
+        ("help", []) => Ok(ConsoleCommand::Help),
+        ("clear", []) => Ok(ConsoleCommand::Clear),
+        ("set", [setting, value]) => {
+            if !setting_names().any(|name| name == *setting) {
+                return Err(format!("unknown setting `{setting}`"));
+            }
+            Ok(ConsoleCommand::Set(setting.to_string(), number(value)?))
+        }
+        ("mode", ["fly"]) => Ok(ConsoleCommand::Mode(MovementMode::Fly)),
+        ("mode", ["walk"]) => Ok(ConsoleCommand::Mode(MovementMode::Walk)),
+        ("basis", ["camera"]) => Ok(ConsoleCommand::Basis(MovementBasis::CameraRelative)),
+        ("basis", ["yaw"]) => Ok(ConsoleCommand::Basis(MovementBasis::YawRelative)),
+        ("basis", ["world"]) => Ok(ConsoleCommand::Basis(MovementBasis::WorldAxes)),
+        ("teleport", [x, y, z]) => Ok(ConsoleCommand::Teleport(Vec3::new(
+            number(x)?,
+            number(y)?,
+            number(z)?,
+        ))),
+        ("snap", [degrees]) => {
+            let degrees = number(degrees)?;
+            if degrees <= 0.0 {
+                return Err("snap angle must be positive".to_string());
+            }
+            Ok(ConsoleCommand::Snap(degrees))
+        }
+        ("cursor", ["grabbed"]) => Ok(ConsoleCommand::Cursor(CursorMode::GrabbedHidden)),
+        ("cursor", ["confined"]) => Ok(ConsoleCommand::Cursor(CursorMode::ConfinedVisible)),
+        ("cursor", ["free"]) => Ok(ConsoleCommand::Cursor(CursorMode::FreeVisible)),
+        ("ease", ["linear"]) => Ok(ConsoleCommand::Ease(EaseKind::Linear)),
+        ("ease", ["smoothstep"]) => Ok(ConsoleCommand::Ease(EaseKind::Smoothstep)),
+        ("ease", ["in_out"]) => Ok(ConsoleCommand::Ease(EaseKind::EaseInOut)),
+        ("ease", ["out_cubic"]) => Ok(ConsoleCommand::Ease(EaseKind::EaseOutCubic)),
+        (
+            "help" | "clear" | "set" | "mode" | "basis" | "teleport" | "snap" | "cursor" | "ease",
+            _,
+        ) => Err(format!("wrong arguments for `{name}`, try `help`")),
+        _ => Err(format!("unknown command `{name}`")),
+    }
+}
+
+fn setting_names() -> impl Iterator<Item = &'static str> {
+    SETTINGS.iter().map(|(name, _)| *name)
+}
+
+fn setting_mut<'a>(free_camera: &'a mut FreeCamera, setting: &str) -> Option<&'a mut f32> {
+    SETTINGS
+        .iter()
+        .find(|(name, _)| *name == setting)
+        .map(|(_, field)| field(free_camera))
+}
+
+#[derive(Component)]
+struct ConsoleRoot;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn spawn_console(mut commands: Commands) {
+    commands.spawn((
+        ConsoleRoot,
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(0),
+            left: px(0),
+            width: percent(100),
+            padding: UiRect::all(px(8)),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.8)),
+        GlobalZIndex(10),
+        Visibility::Hidden,
+        children![(ConsoleText, Text::new(""))],
+    ));
+}
+
+fn read_console_input(
+    mut console: ResMut<Console>,
+    mut keyboard_events: MessageReader<KeyboardInput>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+) {
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        if event.key_code == KeyCode::Backquote {
+            console.open = !console.open;
+            continue;
+        }
+        if !console.open {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                if !line.trim().is_empty() {
+                    console.submitted.push(line);
+                }
+            }
+            Key::Backspace => {
+                console.input.pop();
+            }
+            _ => {
+                if let Some(text) = &event.text {
+                    console
+                        .input
+                        .extend(text.chars().filter(|character| !character.is_control()));
+                }
+            }
+        }
+    }
+
+    // Keep typed keys from also driving the camera and the settings shortcuts
+    if console.open {
+        keys.reset_all();
+    }
+}
+
+fn run_console_commands(
+    mut console: ResMut<Console>,
+    mut camera_query: Query<(&mut Transform, &mut FreeCamera, &mut FreeCameraState)>,
+) {
+    for line in std::mem::take(&mut console.submitted) {
+        console.print(format!("> {line}"));
+
+        let command = match parse_command(&line) {
+            Ok(command) => command,
+            Err(error) => {
+                console.print(format!("error: {error}"));
+                continue;
+            }
+        };
+
+        let Ok((mut transform, mut free_camera, mut free_camera_state)) = camera_query.single_mut()
+        else {
+            console.print("error: no free camera to control");
+            continue;
+        };
+
+        match command {
+            ConsoleCommand::Help => {
+                console.print("commands: help, clear, set <setting> <value>, mode <fly|walk>, basis <camera|yaw|world>, teleport <x> <y> <z>, snap <degrees>, cursor <grabbed|confined|free>, ease <linear|smoothstep|in_out|out_cubic>");
+                console.print(format!(
+                    "settings: {}",
+                    setting_names().collect::<Vec<_>>().join(", ")
+                ));
+            }
+            ConsoleCommand::Clear => console.lines.clear(),
+            ConsoleCommand::Set(setting, value) => {
+                if let Some(field) = setting_mut(&mut free_camera, &setting) {
+                    *field = value;
+                    console.print(format!("{setting} = {value}"));
+                }
+            }
+            ConsoleCommand::Mode(mode) => {
+                free_camera.movement_mode = mode;
+                console.print(format!("mode = {mode:?}"));
+            }
+            ConsoleCommand::Basis(basis) => {
+                free_camera.movement_basis = basis;
+                console.print(format!("basis = {basis:?}"));
+            }
+            ConsoleCommand::Teleport(position) => {
+                transform.translation = position;
+                free_camera_state.velocity = Vec3::ZERO;
+                console.print(format!(
+                    "teleported to {:.02} {:.02} {:.02}",
+                    position.x, position.y, position.z
+                ));
+            }
+            ConsoleCommand::Snap(degrees) => {
+                free_camera_state.snap_yaw(degrees.to_radians());
+                console.print(format!("snapping to {degrees}°"));
+            }
+            ConsoleCommand::Cursor(mode) => {
+                free_camera.cursor_mode = mode;
+                console.print(format!("cursor = {mode:?}"));
+            }
+            ConsoleCommand::Ease(kind) => {
+                free_camera.look_ease = kind;
+                console.print(format!("ease = {kind:?}"));
+            }
+        }
+    }
+}
+
+fn update_console_text(
+    console: Res<Console>,
+    mut root_query: Query<&mut Visibility, With<ConsoleRoot>>,
+    mut text_query: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    for mut visibility in &mut root_query {
+        *visibility = if console.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    for mut text in &mut text_query {
+        text.0 = console
+            .lines
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>()
+            + &format!("> {}_", console.input);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse_command("help"), Ok(ConsoleCommand::Help));
+        assert_eq!(
+            parse_command("  set   sensitivity 0.3 "),
+            Ok(ConsoleCommand::Set("sensitivity".to_string(), 0.3))
+        );
+        assert_eq!(
+            parse_command("mode walk"),
+            Ok(ConsoleCommand::Mode(MovementMode::Walk))
+        );
+        assert_eq!(
+            parse_command("teleport 1 -2 3.5"),
+            Ok(ConsoleCommand::Teleport(Vec3::new(1.0, -2.0, 3.5)))
+        );
+        assert_eq!(parse_command("snap 45"), Ok(ConsoleCommand::Snap(45.0)));
+        assert_eq!(
+            parse_command("cursor free"),
+            Ok(ConsoleCommand::Cursor(CursorMode::FreeVisible))
+        );
+        assert_eq!(
+            parse_command("ease out_cubic"),
+            Ok(ConsoleCommand::Ease(EaseKind::EaseOutCubic))
+        );
+    }
+
+    #[test]
+    fn rejects_non_finite_numbers() {
+        for value in ["NaN", "inf", "-inf", "fast"] {
+            assert!(parse_command(&format!("set friction {value}")).is_err());
+            assert!(parse_command(&format!("teleport 0 {value} 0")).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_settings_and_commands() {
+        assert_eq!(
+            parse_command("set warp_speed 9"),
+            Err("unknown setting `warp_speed`".to_string())
+        );
+        assert_eq!(
+            parse_command("fly"),
+            Err("unknown command `fly`".to_string())
+        );
+        assert_eq!(
+            parse_command("mode swim"),
+            Err("wrong arguments for `mode`, try `help`".to_string())
+        );
+        assert!(parse_command("").is_err());
+    }
+
+    #[test]
+    fn rejects_non_positive_snap_angles() {
+        assert!(parse_command("snap 0").is_err());
+        assert!(parse_command("snap -15").is_err());
+    }
+
+    #[test]
+    fn every_setting_name_writes_its_own_field() {
+        for name in setting_names() {
+            let mut free_camera = FreeCamera::default();
+            *setting_mut(&mut free_camera, name).unwrap() = 123.0;
+            assert_eq!(*setting_mut(&mut free_camera, name).unwrap(), 123.0);
+            assert_eq!(
+                parse_command(&format!("set {name} 1")),
+                Ok(ConsoleCommand::Set(name.to_string(), 1.0))
+            );
+        }
+
+        let mut free_camera = FreeCamera::default();
+        *setting_mut(&mut free_camera, "walk_speed").unwrap() = 7.0;
+        assert_eq!(free_camera.walk_speed, 7.0);
+        assert!(setting_mut(&mut free_camera, "warp_speed").is_none());
+    }
+}
@@ -0,0 +1,773 @@
+//! The corridor and colonnade demo scene.
+//!
+//! [`build_demo_world`] spawns the whole scene under one root entity. The components it puts on
+//! the scene entities are exported too, so an app can find the walls, landmarks and triggers
+//! again, and [`apply_loaded_textures`] has to run in the app for the textures to show up.
+
+use std::path::Path;
+
+use bevy::{
+    asset::AssetLoadFailedEvent,
+    camera::primitives::Aabb,
+    color::palettes::tailwind,
+    image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor},
+    math::Affine2,
+    prelude::*,
+};
+
+use crate::free_camera::CameraCollider;
+
+/// The plain white material the built-in walls share, set by [`build_demo_world`].
+#[derive(Resource)]
+pub struct WhiteMaterial(pub Handle<StandardMaterial>);
+
+/// Something in the scene worth looking at.
+#[derive(Component)]
+pub struct Landmark;
+
+/// Fraction of a LOD distance the camera has to move past it before the level changes, so
+/// hovering right at the boundary doesn't make the mesh flicker.
+const LOD_HYSTERESIS: f32 = 0.05;
+
+/// Levels of detail for a mesh. `meshes[i]` is shown while the camera is closer than
+/// `distances[i]`, beyond the last distance the entity is hidden.
+#[derive(Component, Clone)]
+pub struct Lod {
+    pub distances: Vec<f32>,
+    pub meshes: Vec<Handle<Mesh>>,
+    /// Index into `meshes` currently shown, `meshes.len()` while hidden.
+    pub level: usize,
+}
+
+impl Lod {
+    pub fn new(distances: Vec<f32>, meshes: Vec<Handle<Mesh>>) -> Self {
+        assert_eq!(
+            distances.len(),
+            meshes.len(),
+            "each LOD mesh needs a distance"
+        );
+        Self {
+            distances,
+            meshes,
+            level: 0,
+        }
+    }
+
+    /// Level to show at `distance`, only moving away from the current one once the distance
+    /// is clearly past the boundary.
+    pub fn level_at(&self, distance: f32) -> usize {
+        let mut level = self.level.min(self.distances.len());
+        while level > 0 && distance < self.distances[level - 1] * (1.0 - LOD_HYSTERESIS) {
+            level -= 1;
+        }
+        while level < self.distances.len()
+            && distance > self.distances[level] * (1.0 + LOD_HYSTERESIS)
+        {
+            level += 1;
+        }
+        level
+    }
+}
+
+/// The textured sky from the scene, shown when the gradient sky is off.
+#[derive(Component)]
+pub struct SkyboxCircle;
+
+/// Marks scene entities whose materials follow the lighting mode. The skies are left out.
+#[derive(Component, Clone, Copy)]
+pub struct SceneMaterial;
+
+/// Emissive colour of the floor, which the fog takes its colour from.
+pub const FLOOR_EMISSIVE: LinearRgba = LinearRgba::rgb(0.244, 0.166, 0.172);
+
+/// Marks the wall entities, so the floor and the skies keep their own surface.
+#[derive(Component)]
+pub struct WallMaterial;
+
+/// Volume that fires its payload when the camera enters it. The volume is the entity's [`Aabb`]
+/// in world space.
+#[derive(Component)]
+pub struct Trigger {
+    pub payload: String,
+    /// Whether the camera was inside last frame, so a trigger fires once per entry.
+    pub inside: bool,
+}
+
+impl Trigger {
+    pub fn new(payload: impl Into<String>) -> Self {
+        Self {
+            payload: payload.into(),
+            inside: false,
+        }
+    }
+}
+
+/// Positional looping sound. Its volume falls from `volume` at the source to silence at
+/// `max_distance`, on top of the panning done by spatial audio.
+#[derive(Component)]
+#[require(Transform)]
+pub struct AmbientSound {
+    pub volume: f32,
+    pub max_distance: f32,
+    /// Shape of the falloff, 1.0 is linear and higher values drop off faster near the source.
+    pub rolloff: f32,
+}
+
+impl AmbientSound {
+    /// Volume heard at `distance` from the source.
+    pub fn gain(&self, distance: f32) -> f32 {
+        if self.max_distance <= 0.0 {
+            return 0.0;
+        }
+        (1.0 - distance / self.max_distance)
+            .clamp(0.0, 1.0)
+            .powf(self.rolloff)
+            * self.volume
+    }
+}
+
+/// Sound player for an ambient sound, to be spawned together with [`AmbientSound`].
+pub fn ambient_sound_player(sound: Handle<AudioSource>) -> (AudioPlayer, PlaybackSettings) {
+    (
+        AudioPlayer::new(sound),
+        PlaybackSettings::LOOP.with_spatial(true),
+    )
+}
+
+/// Color of materials whose texture failed to load.
+const MISSING_TEXTURE_COLOR: Color = Color::srgb(1.0, 0.0, 1.0);
+
+/// Material waiting for its texture. Until the texture has loaded the material shows a solid
+/// placeholder color, so large textures don't hold up the first frames.
+#[derive(Component)]
+pub struct PendingTexture {
+    texture: Handle<Image>,
+    material: Handle<StandardMaterial>,
+    /// Base color the material gets back once the texture is in place.
+    base_color: Color,
+}
+
+/// Adds `material` with its base color texture swapped for `placeholder` until the texture loads.
+fn add_textured_material(
+    commands: &mut Commands,
+    materials: &mut Assets<StandardMaterial>,
+    material: StandardMaterial,
+    placeholder: Color,
+) -> Handle<StandardMaterial> {
+    let Some(texture) = material.base_color_texture.clone() else {
+        return materials.add(material);
+    };
+
+    let base_color = material.base_color;
+    let handle = materials.add(StandardMaterial {
+        base_color: placeholder,
+        base_color_texture: None,
+        ..material
+    });
+    commands.spawn(PendingTexture {
+        texture,
+        material: handle.clone(),
+        base_color,
+    });
+    handle
+}
+
+/// Puts loaded textures on the materials waiting for them, and marks the materials whose
+/// texture failed to load with a solid, unlit color.
+pub fn apply_loaded_textures(
+    mut commands: Commands,
+    mut loaded_events: MessageReader<AssetEvent<Image>>,
+    mut failed_events: MessageReader<AssetLoadFailedEvent<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    pending_query: Query<(Entity, &PendingTexture)>,
+) {
+    let failed: Vec<_> = failed_events.read().collect();
+    for event in loaded_events.read() {
+        let AssetEvent::LoadedWithDependencies { id } = *event else {
+            continue;
+        };
+        for (entity, pending) in &pending_query {
+            if pending.texture.id() != id {
+                continue;
+            }
+            if let Some(material) = materials.get_mut(&pending.material) {
+                material.base_color = pending.base_color;
+                material.base_color_texture = Some(pending.texture.clone());
+            }
+            commands.entity(entity).despawn();
+        }
+    }
+
+    // Make failed textures stand out instead of leaving a plausible looking placeholder
+    for event in failed {
+        for (entity, pending) in &pending_query {
+            if pending.texture.id() != event.id {
+                continue;
+            }
+            warn!(
+                "Could not load texture {}, using the missing texture color: {}",
+                event.path, event.error
+            );
+            if let Some(material) = materials.get_mut(&pending.material) {
+                material.base_color = MISSING_TEXTURE_COLOR;
+                material.unlit = true;
+            }
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Arrangement of the marble columns, all drawn from one mesh and material. The pattern is a
+/// grid seen from above, one line per row along +Z and one character per column along +X, with
+/// `#` marking a column and anything else a gap.
+struct ColumnLayout {
+    /// Position of the first cell of the first row.
+    origin: Vec3,
+    /// Distance between neighbouring cells.
+    spacing: f32,
+    pattern: &'static str,
+}
+
+impl Default for ColumnLayout {
+    fn default() -> Self {
+        // The colonnade by the fountain, a ring with a diagonal through it
+        Self {
+            origin: Vec3::new(9.0, 0.0, 22.0),
+            spacing: 1.0,
+            pattern: "
+                ##..#
+                #.#.#
+                #..##
+                #####
+            ",
+        }
+    }
+}
+
+impl ColumnLayout {
+    /// Positions of the columns, row by row. Blank lines and indentation are ignored.
+    fn positions(&self) -> impl Iterator<Item = Vec3> + '_ {
+        self.pattern
+            .lines()
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .enumerate()
+            .flat_map(move |(row, cells)| {
+                cells
+                    .chars()
+                    .enumerate()
+                    .filter(|(_, cell)| *cell == '#')
+                    .map(move |(column, _)| {
+                        self.origin + Vec3::new(column as f32, 0.0, row as f32) * self.spacing
+                    })
+            })
+    }
+}
+
+/// Spawns a copy of `extra` under `parent` for each transform, all sharing one mesh and material.
+/// Bevy draws entities with the same mesh and material handles as instances of a single batch,
+/// so repeated props should reuse handles like this instead of adding new assets per copy.
+fn spawn_instances(
+    commands: &mut Commands,
+    parent: Entity,
+    mesh: &Handle<Mesh>,
+    material: &Handle<StandardMaterial>,
+    transforms: impl IntoIterator<Item = Transform>,
+    extra: impl Bundle + Clone,
+) {
+    for transform in transforms {
+        commands.spawn((
+            ChildOf(parent),
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            transform,
+            extra.clone(),
+        ));
+    }
+}
+
+/// Rotation that turns `yaw_degrees` to the left, counterclockwise seen from above, and then
+/// pitches `pitch_degrees` up. Something facing forward (-Z) like a camera ends up looking that
+/// way, e.g. `orient(90.0, 0.0)` looks along -X.
+fn orient(yaw_degrees: f32, pitch_degrees: f32) -> Quat {
+    Quat::from_rotation_y(yaw_degrees.to_radians())
+        * Quat::from_rotation_x(pitch_degrees.to_radians())
+}
+
+/// Rotation that turns the +Z side of a shape towards `direction`. That is the side flat
+/// primitives like `Circle` are visible from, and the face of a wall modelled thin along Z.
+pub fn face_direction(direction: Dir3) -> Quat {
+    // +Z facing `direction` is the same as forward facing away from it
+    orient(
+        direction.x.atan2(direction.z).to_degrees(),
+        (-direction.y).asin().to_degrees(),
+    )
+}
+
+/// Spawns the corridor and colonnade demo scene under a new root entity, scaled by `world_scale`
+/// world units per meter, and returns the root.
+///
+/// The textures `marble.png`, `floor.png` and `skybox.png` are loaded from `texture_root` in the
+/// asset folder, and the fountain plays `sounds/fountain.ogg`. Materials start out with flat
+/// placeholder colours until their texture loads, which takes [`apply_loaded_textures`] running
+/// in the app, and keep them if a texture is missing. The plain white wall material is also
+/// inserted as the [`WhiteMaterial`] resource, so walls placed later can share it.
+pub fn build_demo_world(
+    commands: &mut Commands,
+    materials: &mut Assets<StandardMaterial>,
+    meshes: &mut Assets<Mesh>,
+    asset_server: &AssetServer,
+    world_scale: f32,
+    texture_root: &Path,
+) -> Entity {
+    // Everything is spawned under one root so the whole scene follows the world scale
+    let world = commands
+        .spawn((
+            Transform::from_scale(Vec3::splat(world_scale)),
+            Visibility::default(),
+        ))
+        .id();
+
+    let floor = meshes.add(Plane3d::new(
+        Vec3::new(0.0, 100.0, 0.0),
+        Vec2::new(20.0, 35.0),
+    ));
+    let sphere = meshes.add(Sphere::new(0.5));
+
+    let wall = meshes.add(Cuboid::new(0.2, 4.0, 3.0));
+    let back_wall = meshes.add(Cuboid::new(50.0, 5.0, 0.35));
+    let cub_wall = meshes.add(Cuboid::new(5.0, 5.0, 0.2));
+    let tav_wall = meshes.add(Cuboid::new(9.0, 5.0, 0.35));
+
+    let long_wall = meshes.add(Cuboid::new(80.0, 5.0, 0.35));
+    let cub_ent = meshes.add(Cuboid::new(2.0, 5.0, 0.15));
+    let shor_ent = meshes.add(Cuboid::new(1.0, 5.0, 0.15));
+
+    let hall_1 = meshes.add(Cuboid::new(5.0, 5.0, 0.15));
+
+    let column = meshes.add(Cylinder::new(0.3, 5.0));
+    let column_low = meshes.add(Cylinder::new(0.3, 5.0).mesh().resolution(8));
+    let blue_material = materials.add(Color::from(tailwind::BLUE_700));
+    let red_material = materials.add(Color::from(tailwind::RED_950));
+    let white_material = materials.add(Color::WHITE);
+    commands.insert_resource(WhiteMaterial(white_material.clone()));
+    let texture_handle = asset_server.load(texture_root.join("marble.png"));
+    let skyeee = asset_server.load(texture_root.join("skybox.png"));
+    let floa = asset_server.load(texture_root.join("floor.png"));
+
+    let material_handle = add_textured_material(
+        commands,
+        materials,
+        StandardMaterial {
+            base_color_texture: Some(texture_handle.clone()),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        },
+        Color::from(tailwind::STONE_300),
+    );
+    let flooo = add_textured_material(
+        commands,
+        materials,
+        StandardMaterial {
+            base_color_texture: Some(floa.clone()),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        },
+        Color::from(tailwind::STONE_600),
+    );
+    let skybox = add_textured_material(
+        commands,
+        materials,
+        StandardMaterial {
+            base_color_texture: Some(skyeee.clone()),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        },
+        Color::from(tailwind::SKY_300),
+    );
+    let sky = meshes.add(Circle::new(100.0));
+    // Top side of floor
+
+    let floor_material = add_textured_material(
+        commands,
+        materials,
+        StandardMaterial {
+            base_color_texture: Some(asset_server.load_with_settings(
+                texture_root.join("floor.png"),
+                |s: &mut _| {
+                    *s = ImageLoaderSettings {
+                        sampler: ImageSampler::Descriptor(ImageSamplerDescriptor {
+                            // rewriting mode to repeat image,
+                            address_mode_u: ImageAddressMode::MirrorRepeat,
+                            address_mode_v: ImageAddressMode::MirrorRepeat,
+
+                            ..default()
+                        }),
+
+                        ..default()
+                    }
+                },
+            )),
+            emissive: FLOOR_EMISSIVE,
+            // uv_transform used here for proportions only, but it is full Affine2
+            // that's why you can use rotation and shift also
+            uv_transform: Affine2::from_scale(Vec2::new(20., 20.)),
+            ..default()
+        },
+        Color::from(tailwind::STONE_600),
+    );
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(floor.clone()),
+        MeshMaterial3d(floor_material),
+        CameraCollider::default(),
+    ));
+
+    // Tall wall
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(wall.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform::from_xyz(-3.0, 2.0, 0.0),
+    ));
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(long_wall.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform {
+            translation: Vec3::new(20.0, 0.0, 0.0),
+            rotation: face_direction(Dir3::X),
+            ..default()
+        },
+    ));
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(long_wall.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform {
+            translation: Vec3::new(0.0, 0.0, 0.0),
+            rotation: face_direction(Dir3::X),
+            ..default()
+        },
+    ));
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(back_wall.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform::from_xyz(0.0, 0.0, 35.0),
+    ));
+
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(cub_wall.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform::from_xyz(18.0, 0.0, 27.0),
+    ));
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(cub_wall.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform::from_xyz(18.0, 0.0, 23.0),
+    ));
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(cub_wall.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform::from_xyz(18.0, 0.0, 20.0),
+    ));
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(cub_wall.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform::from_xyz(18.0, 0.0, 16.0),
+    ));
+
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(cub_wall.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform::from_xyz(18.0, 0.0, 14.0),
+    ));
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(cub_wall.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform::from_xyz(18.0, 0.0, 9.0),
+    ));
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(cub_wall.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform::from_xyz(18.0, 0.0, 5.0),
+    ));
+
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(cub_ent.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform {
+            translation: Vec3::new(15.5, 0.0, 26.0),
+            rotation: face_direction(Dir3::X),
+            ..default()
+        },
+    ));
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(cub_ent.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform {
+            translation: Vec3::new(15.5, 0.0, 23.0),
+            rotation: face_direction(Dir3::X),
+            ..default()
+        },
+    ));
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(cub_ent.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform {
+            translation: Vec3::new(15.5, 0.0, 20.0),
+            rotation: face_direction(Dir3::X),
+            ..default()
+        },
+    ));
+
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(hall_1.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform {
+            translation: Vec3::new(15.5, 0.0, 11.5),
+            rotation: face_direction(Dir3::X),
+            ..default()
+        },
+    ));
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(shor_ent.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform {
+            translation: Vec3::new(15.5, 0.0, 13.0),
+            rotation: face_direction(Dir3::X),
+            ..default()
+        },
+    ));
+
+    commands.spawn((
+        ChildOf(world),
+        SceneMaterial,
+        Mesh3d(tav_wall.clone()),
+        MeshMaterial3d(white_material.clone()),
+        WallMaterial,
+        CameraCollider::default(),
+        Transform::from_xyz(16.0, 0.0, 0.0),
+    ));
+
+    spawn_instances(
+        commands,
+        world,
+        &column,
+        &material_handle,
+        ColumnLayout::default()
+            .positions()
+            .map(Transform::from_translation),
+        (
+            CameraCollider::default(),
+            SceneMaterial,
+            Lod::new(
+                vec![25.0 * world_scale, 60.0 * world_scale],
+                vec![column.clone(), column_low],
+            ),
+        ),
+    );
+
+    // Fountain by the columns
+    commands.spawn((
+        ChildOf(world),
+        Landmark,
+        AmbientSound {
+            volume: 0.8,
+            max_distance: 15.0 * world_scale,
+            rolloff: 2.0,
+        },
+        ambient_sound_player(asset_server.load("sounds/fountain.ogg")),
+        Transform::from_xyz(11.0, 1.0, 23.5),
+    ));
+
+    // End of the corridor
+    commands.spawn((
+        ChildOf(world),
+        Landmark,
+        Trigger::new("reached the end of the corridor"),
+        Aabb::from_min_max(Vec3::new(-5.0, -2.5, -2.0), Vec3::new(5.0, 2.5, 2.0)),
+        Transform::from_xyz(5.0, 2.5, 33.0),
+    ));
+
+    commands.spawn((
+        ChildOf(world),
+        SkyboxCircle,
+        Mesh3d(sky.clone()),
+        MeshMaterial3d(skybox.clone()),
+        Transform {
+            translation: Vec3::new(0.0, 65.0, 0.0),
+            rotation: face_direction(Dir3::NEG_Y),
+            ..default()
+        },
+    ));
+
+    world
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetLoadError;
+    use bevy::asset::io::AssetReaderError;
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn missing_texture_falls_back_to_the_missing_texture_color() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<StandardMaterial>();
+        let world = app.world_mut();
+        let texture = world.resource::<Assets<Image>>().reserve_handle();
+        let material =
+            world.resource_scope(|world, mut materials: Mut<Assets<StandardMaterial>>| {
+                add_textured_material(
+                    &mut world.commands(),
+                    &mut materials,
+                    StandardMaterial {
+                        base_color_texture: Some(texture.clone()),
+                        ..default()
+                    },
+                    Color::WHITE,
+                )
+            });
+        world.flush();
+
+        let path = "textures/does_not_exist.png";
+        world.write_message(AssetLoadFailedEvent::<Image> {
+            id: texture.id(),
+            path: path.into(),
+            error: AssetLoadError::AssetReaderError(AssetReaderError::NotFound(path.into())),
+        });
+        world.run_system_once(apply_loaded_textures).unwrap();
+
+        let material = world
+            .resource::<Assets<StandardMaterial>>()
+            .get(&material)
+            .unwrap();
+        assert_eq!(material.base_color, MISSING_TEXTURE_COLOR);
+        assert!(material.base_color_texture.is_none());
+        let mut pending = world.query::<&PendingTexture>();
+        assert_eq!(pending.iter(world).count(), 0);
+    }
+
+    #[test]
+    fn orient_turns_forward_by_yaw_then_pitch() {
+        assert!((orient(0.0, 0.0) * Vec3::NEG_Z).abs_diff_eq(Vec3::NEG_Z, 1e-6));
+        assert!((orient(90.0, 0.0) * Vec3::NEG_Z).abs_diff_eq(Vec3::NEG_X, 1e-6));
+        assert!((orient(-90.0, 0.0) * Vec3::NEG_Z).abs_diff_eq(Vec3::X, 1e-6));
+        assert!((orient(0.0, 90.0) * Vec3::NEG_Z).abs_diff_eq(Vec3::Y, 1e-6));
+
+        let forward = orient(90.0, 45.0) * Vec3::NEG_Z;
+        let expected = Vec3::new(-1.0, 1.0, 0.0).normalize();
+        assert!(forward.abs_diff_eq(expected, 1e-6));
+    }
+
+    #[test]
+    fn face_direction_turns_plus_z_towards_the_direction() {
+        let directions = [
+            Dir3::X,
+            Dir3::NEG_X,
+            Dir3::Y,
+            Dir3::NEG_Y,
+            Dir3::Z,
+            Dir3::NEG_Z,
+            Dir3::new(Vec3::new(1.0, -2.0, 3.0)).unwrap(),
+        ];
+        for direction in directions {
+            let facing = face_direction(direction) * Vec3::Z;
+            assert!(facing.abs_diff_eq(*direction, 1e-5), "{direction:?}");
+        }
+    }
+
+    #[test]
+    fn column_layout_places_a_column_per_hash() {
+        let layout = ColumnLayout {
+            origin: Vec3::new(1.0, 0.0, 2.0),
+            spacing: 2.0,
+            pattern: "
+                #.#
+
+                .#
+            ",
+        };
+        let positions: Vec<Vec3> = layout.positions().collect();
+        assert_eq!(
+            positions,
+            [
+                Vec3::new(1.0, 0.0, 2.0),
+                Vec3::new(5.0, 0.0, 2.0),
+                Vec3::new(3.0, 0.0, 4.0),
+            ]
+        );
+
+        assert_eq!(ColumnLayout::default().positions().count(), 14);
+    }
+}
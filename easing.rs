@@ -0,0 +1,87 @@
+//! Easing curves shared by the camera tweens and scene animations.
+//!
+//! Every curve maps `0.0` to `0.0` and `1.0` to `1.0`. Use [`apply_ease`] to pick one by
+//! [`EaseKind`], or call the individual functions directly.
+
+/// Shape of the curve used to ease an animation from start to end.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EaseKind {
+    /// Constant rate from start to end.
+    Linear,
+    /// Starts and ends gently. See [`smoothstep`].
+    #[default]
+    Smoothstep,
+    /// Starts and ends gently, with a quicker middle than [`EaseKind::Smoothstep`].
+    /// See [`ease_in_out`].
+    EaseInOut,
+    /// Starts quickly and settles gently. See [`ease_out_cubic`].
+    EaseOutCubic,
+}
+
+/// Eases `t` with the curve of the given `kind`.
+///
+/// `t` is clamped to `0.0..=1.0` first, so callers can pass an unclamped progress.
+pub fn apply_ease(kind: EaseKind, t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    match kind {
+        EaseKind::Linear => t,
+        EaseKind::Smoothstep => smoothstep(t),
+        EaseKind::EaseInOut => ease_in_out(t),
+        EaseKind::EaseOutCubic => ease_out_cubic(t),
+    }
+}
+
+/// Hermite smoothstep, `3t² - 2t³`.
+pub fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Cubic ease in and out: accelerates over the first half and decelerates over the second.
+pub fn ease_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+    }
+}
+
+/// Cubic ease out: starts at full rate and slows to a stop.
+pub fn ease_out_cubic(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KINDS: [EaseKind; 4] = [
+        EaseKind::Linear,
+        EaseKind::Smoothstep,
+        EaseKind::EaseInOut,
+        EaseKind::EaseOutCubic,
+    ];
+
+    #[test]
+    fn every_curve_starts_at_zero_and_ends_at_one() {
+        for kind in KINDS {
+            assert_eq!(apply_ease(kind, 0.0), 0.0, "{kind:?}");
+            assert_eq!(apply_ease(kind, 1.0), 1.0, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn halfway_values() {
+        assert_eq!(apply_ease(EaseKind::Linear, 0.5), 0.5);
+        assert_eq!(apply_ease(EaseKind::Smoothstep, 0.5), 0.5);
+        assert_eq!(apply_ease(EaseKind::EaseInOut, 0.5), 0.5);
+        assert_eq!(apply_ease(EaseKind::EaseOutCubic, 0.5), 0.875);
+    }
+
+    #[test]
+    fn out_of_range_progress_is_clamped() {
+        for kind in KINDS {
+            assert_eq!(apply_ease(kind, -1.0), 0.0, "{kind:?}");
+            assert_eq!(apply_ease(kind, 2.0), 1.0, "{kind:?}");
+        }
+    }
+}
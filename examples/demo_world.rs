@@ -0,0 +1,47 @@
+//! Builds the demo world in a bare app, with nothing but a free camera to look around it.
+//!
+//! Run it with `cargo run --example demo_world`. The textures are loaded from `assets/textures`.
+
+use std::path::Path;
+
+use bevy::prelude::*;
+use villa_bevy::demo_world::{apply_loaded_textures, build_demo_world};
+use villa_bevy::free_camera::{FreeCamera, FreeCameraPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, FreeCameraPlugin::default()))
+        .add_systems(Startup, setup)
+        // Swaps the placeholder colours for the textures once they have loaded
+        .add_systems(Update, apply_loaded_textures)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    asset_server: Res<AssetServer>,
+) {
+    build_demo_world(
+        &mut commands,
+        &mut materials,
+        &mut meshes,
+        &asset_server,
+        1.0,
+        Path::new("textures"),
+    );
+
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 1.0, 0.0).looking_to(Vec3::X, Vec3::Y),
+        FreeCamera::default(),
+    ));
+    commands.spawn((
+        DirectionalLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
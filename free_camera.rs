@@ -0,0 +1,3920 @@
+//! A camera controller that allows the user to move freely around the scene.
+//!
+//! Free cameras are helpful for exploring large scenes, level editors and for debugging.
+//! They are rarely useful as-is for gameplay,
+//! as they allow the user to move freely in all directions,
+//! which can be disorienting, and they can clip through objects.
+//!
+//! To use this controller, add [`FreeCameraPlugin`] to your app,
+//! and insert a [`FreeCamera`] component into your camera entity.
+//!
+//! To configure the settings of this controller, modify the fields of the [`FreeCamera`] component.
+//! The controls are looked up by [`Action`] in the [`InputMap<Action>`] resource, which can be
+//! changed to rebind them.
+
+use std::{collections::HashMap, f32::consts::*, fmt, hash::Hash, time::Duration};
+
+use bevy::{
+    camera::primitives::Aabb,
+    ecs::system::SystemParam,
+    input::{
+        InputSystems,
+        mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit},
+    },
+    math::bounding::{Aabb3d, BoundingVolume, RayCast3d},
+    prelude::*,
+    window::{CursorGrabMode, CursorOptions, WindowFocused},
+};
+
+use crate::easing::{EaseKind, apply_ease};
+
+/// A freecam-style camera controller plugin.
+///
+/// Use the [`FreeCamera`] struct to add and customize the controller for a camera entity.
+/// The camera's dynamic state is managed by the [`FreeCameraState`] struct.
+///
+/// The fields of the plugin hold settings shared by every controller in the app. Use
+/// `FreeCameraPlugin::default()` for the standard behavior.
+#[derive(Clone, Debug, Default)]
+pub struct FreeCameraPlugin {
+    /// Moves the controller into [`FixedUpdate`] so camera motion is deterministic, and adds a
+    /// [`PreviousTransform`] to every [`FreeCamera`] to keep the rendered motion smooth.
+    ///
+    /// Input is still collected once per frame: mouse motion, scrolling and presses are
+    /// applied by the first tick of a frame, and can be missed in a frame without a tick.
+    /// [`FreeCamera::input_buffer_ms`] keeps short taps on the movement keys from being lost.
+    /// Fixed time follows [`Time<Virtual>`], so the camera stops while it is paused.
+    pub run_in_fixed_update: bool,
+    /// Grabs the cursor as soon as a controller starts, as if
+    /// [`Action::ToggleCursorGrab`] had been pressed.
+    ///
+    /// Browsers only grant pointer lock in response to a user gesture, so on the web the
+    /// cursor is grabbed on the first click on the canvas instead.
+    pub capture_cursor_on_start: bool,
+    /// Controls inserted as the [`InputMap<Action>`] resource, replacing any map inserted
+    /// before the plugin was added.
+    pub default_bindings: InputMap<Action>,
+}
+
+/// The [`FreeCameraPlugin`] settings the controller systems read at runtime, inserted by the
+/// plugin.
+#[derive(Resource, Clone, Copy)]
+pub struct FreeCameraSettings {
+    run_in_fixed_update: bool,
+    capture_cursor_on_start: bool,
+}
+
+impl Plugin for FreeCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.default_bindings.clone())
+            .insert_resource(FreeCameraSettings {
+                run_in_fixed_update: self.run_in_fixed_update,
+                capture_cursor_on_start: self.capture_cursor_on_start,
+            });
+
+        // The sets run in this order in every schedule the controller uses
+        let sets = || {
+            (
+                FreeCameraSystems::Move,
+                FreeCameraSystems::Look,
+                FreeCameraSystems::ApplyTransform,
+            )
+                .chain()
+        };
+        app.configure_sets(FixedUpdate, sets())
+            .configure_sets(RunFixedMainLoop, sets());
+
+        // This ordering is required so that both fixed update and update systems can see the results correctly
+        if self.run_in_fixed_update {
+            app.add_systems(
+                FixedUpdate,
+                (
+                    run_freecamera_controller.in_set(FreeCameraSystems::Move),
+                    run_freecamera_look_at.in_set(FreeCameraSystems::Look),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                RunFixedMainLoop,
+                (
+                    run_freecamera_nudge.in_set(FreeCameraSystems::Move),
+                    run_freecamera_sprint_fov,
+                )
+                    .chain()
+                    .in_set(RunFixedMainLoopSystems::BeforeFixedMainLoop),
+            )
+            .add_systems(
+                RunFixedMainLoop,
+                interpolate_fixed_transform
+                    .in_set(FreeCameraSystems::ApplyTransform)
+                    .in_set(RunFixedMainLoopSystems::AfterFixedMainLoop),
+            )
+            .add_observer(add_previous_transform);
+        } else {
+            app.add_systems(
+                RunFixedMainLoop,
+                (
+                    run_freecamera_controller.in_set(FreeCameraSystems::Move),
+                    run_freecamera_look_at.in_set(FreeCameraSystems::Look),
+                    run_freecamera_nudge.in_set(FreeCameraSystems::ApplyTransform),
+                    run_freecamera_sprint_fov,
+                )
+                    .chain()
+                    .in_set(RunFixedMainLoopSystems::BeforeFixedMainLoop),
+            )
+            .add_systems(
+                RunFixedMainLoop,
+                interpolate_fixed_transform.in_set(RunFixedMainLoopSystems::AfterFixedMainLoop),
+            );
+        }
+
+        app.add_systems(PreUpdate, buffer_movement_input.after(InputSystems))
+            .add_systems(FixedFirst, begin_fixed_transform)
+            .add_systems(FixedLast, end_fixed_transform);
+    }
+}
+
+/// System sets the [`FreeCameraPlugin`] runs its systems in, in the order listed.
+///
+/// Order your own systems against these, e.g. `.after(FreeCameraSystems::ApplyTransform)` to
+/// attach something that follows the camera.
+///
+/// Without [`run_in_fixed_update`](FreeCameraPlugin::run_in_fixed_update) all three sets run in
+/// [`RunFixedMainLoop`], inside [`RunFixedMainLoopSystems::BeforeFixedMainLoop`]. With it,
+/// [`Move`](FreeCameraSystems::Move) and [`Look`](FreeCameraSystems::Look) run in
+/// [`FixedUpdate`], and [`ApplyTransform`](FreeCameraSystems::ApplyTransform) interpolates the
+/// rendered transform in [`RunFixedMainLoopSystems::AfterFixedMainLoop`]. Either way the
+/// [`Transform`] is final for the frame once `ApplyTransform` has run, so anything in
+/// [`Update`] already sees it.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FreeCameraSystems {
+    /// Reads input, turns the camera and moves it, including collisions.
+    ///
+    /// Stepping with the nudge keys is part of this set too when running in fixed update.
+    Move,
+    /// Aims cameras that have a [`LookAtTarget`], overriding the mouse look.
+    Look,
+    /// Writes the last changes to the [`Transform`]: the nudge steps, or in fixed update the
+    /// interpolation between ticks.
+    ApplyTransform,
+}
+
+/// Scales mouse motion into yaw/pitch movement.
+///
+/// Based on Valorant's default sensitivity, not entirely sure why it is exactly 1.0 / 180.0,
+/// but we're guessing it is a misunderstanding between degrees/radians and then sticking with
+/// it because it felt nice.
+const RADIANS_PER_DOT: f32 = 1.0 / 180.0;
+
+/// Gap kept between the camera body and a collider after resolving a collision.
+const COLLISION_SKIN: f32 = 1e-3;
+
+/// How the [`FreeCamera`] moves through the scene.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MovementMode {
+    /// Move freely along the camera's view direction, including up and down.
+    #[default]
+    Fly,
+    /// Move along the ground under gravity.
+    ///
+    /// Movement stays horizontal, [`MovementBasis::CameraRelative`] behaves like
+    /// [`MovementBasis::YawRelative`] in this mode.
+    ///
+    /// [`Action::MoveUp`] jumps while grounded. This mode needs
+    /// [`CameraCollider`]s to stand on, without them the camera keeps falling.
+    Walk,
+}
+
+/// Which directions the movement keys map to in world space.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MovementBasis {
+    /// Follow the camera's full orientation, so looking down and moving forward descends.
+    #[default]
+    CameraRelative,
+    /// Follow only the camera's yaw, keeping forward and sideways movement horizontal.
+    YawRelative,
+    /// Ignore the camera entirely: forward is -Z and right is +X, tilted along with
+    /// [`FreeCamera::up`].
+    WorldAxes,
+}
+
+impl MovementBasis {
+    /// Returns the world space forward and right directions for a camera with the given
+    /// `rotation` and `yaw`.
+    ///
+    /// `up_rotation` maps world Y onto the camera's up direction, see
+    /// [`FreeCamera::up_rotation`].
+    pub fn axes(self, rotation: Quat, yaw: f32, up_rotation: Quat) -> (Vec3, Vec3) {
+        match self {
+            MovementBasis::CameraRelative => (rotation * Vec3::NEG_Z, rotation * Vec3::X),
+            MovementBasis::YawRelative => {
+                let yaw = up_rotation * Quat::from_rotation_y(yaw);
+                (yaw * Vec3::NEG_Z, yaw * Vec3::X)
+            }
+            MovementBasis::WorldAxes => (up_rotation * Vec3::NEG_Z, up_rotation * Vec3::X),
+        }
+    }
+}
+
+/// Set of controls a [`FreeCamera`] responds to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ControlScheme {
+    /// Fly or walk with the movement keys and look around while the cursor is grabbed.
+    #[default]
+    Fps,
+    /// Turn around a focus point in front of the camera while the cursor is grabbed, and
+    /// zoom towards it with the scroll wheel. The movement keys are ignored.
+    Orbit,
+    /// Like [`Orbit`](ControlScheme::Orbit), but turning happens while
+    /// [`Action::Orbit`] is held rather than with the cursor grabbed, and dragging with
+    /// [`Action::Pan`] held slides the camera in its view plane.
+    Cad,
+}
+
+/// What happens to the cursor while it is grabbed for looking around.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CursorMode {
+    /// Lock the cursor in place and hide it.
+    #[default]
+    GrabbedHidden,
+    /// Keep the cursor inside the window and leave it visible, e.g. to check UI coordinates
+    /// while looking around.
+    ConfinedVisible,
+    /// Leave the cursor free and visible. Looking around still follows the mouse, but the
+    /// cursor can leave the window.
+    FreeVisible,
+}
+
+impl CursorMode {
+    /// Returns the grab mode and visibility the cursor gets while grabbed.
+    pub fn grabbed_cursor(self) -> (CursorGrabMode, bool) {
+        match self {
+            CursorMode::GrabbedHidden => (CursorGrabMode::Locked, false),
+            CursorMode::ConfinedVisible => (CursorGrabMode::Confined, true),
+            CursorMode::FreeVisible => (CursorGrabMode::None, true),
+        }
+    }
+}
+
+/// What the mouse scroll wheel adjusts on a [`FreeCamera`], chosen by the modifier keys held.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollTarget {
+    /// [`FreeCameraState::speed_multiplier`], used when no modifier is held.
+    Speed,
+    /// [`FreeCameraState::focus_distance`], used instead of [`Speed`](ScrollTarget::Speed)
+    /// by the orbiting [`ControlScheme`]s.
+    Zoom,
+    /// The field of view of a perspective projection, while [`Action::ScrollFov`] is held.
+    Fov,
+    /// The near clipping plane of a perspective projection, while [`Action::ScrollNear`] is
+    /// held.
+    NearPlane,
+}
+
+impl ScrollTarget {
+    /// Returns `true` for the targets picked by holding a modifier action.
+    pub fn is_modified(self) -> bool {
+        matches!(self, ScrollTarget::Fov | ScrollTarget::NearPlane)
+    }
+}
+
+/// Something the user can do with a [`FreeCamera`], bound to inputs by an [`InputMap`].
+///
+/// Mouse motion and scrolling are read directly and are not actions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Translate forward.
+    MoveForward,
+    /// Translate backward.
+    MoveBack,
+    /// Translate left.
+    MoveLeft,
+    /// Translate right.
+    MoveRight,
+    /// Translate up, or jump in [`MovementMode::Walk`].
+    MoveUp,
+    /// Translate down.
+    MoveDown,
+    /// Use [`run_speed`](FreeCamera::run_speed) instead of
+    /// [`walk_speed`](FreeCamera::walk_speed) while held.
+    Run,
+    /// Crouch while held in [`MovementMode::Walk`].
+    Crouch,
+    /// Turn left by one [`snap_increment`](FreeCamera::snap_increment).
+    SnapLeft,
+    /// Turn right by one [`snap_increment`](FreeCamera::snap_increment).
+    SnapRight,
+    /// Make the scroll wheel adjust the field of view instead of the speed while held.
+    ///
+    /// Shares its default key with [`Run`](Action::Run), and [`ScrollNear`](Action::ScrollNear)
+    /// shares its one with [`Crouch`](Action::Crouch). In a frame with scrolling to either of
+    /// them, running and crouching stay as they were.
+    ScrollFov,
+    /// Make the scroll wheel adjust the near clipping plane instead of the speed while held.
+    ///
+    /// [`ScrollFov`](Action::ScrollFov) wins if both are held.
+    ScrollNear,
+    /// Grab the cursor while held.
+    GrabCursor,
+    /// Toggle the cursor grab.
+    ToggleCursorGrab,
+    /// Turn around the focus while held in [`ControlScheme::Cad`].
+    Orbit,
+    /// Pan while held in [`ControlScheme::Cad`].
+    Pan,
+    /// Nudge by [`nudge_step`](FreeCamera::nudge_step) along world -X.
+    NudgeLeft,
+    /// Nudge by [`nudge_step`](FreeCamera::nudge_step) along world +X.
+    NudgeRight,
+    /// Nudge by [`nudge_step`](FreeCamera::nudge_step) along world -Z.
+    NudgeForward,
+    /// Nudge by [`nudge_step`](FreeCamera::nudge_step) along world +Z.
+    NudgeBack,
+    /// Nudge by [`nudge_step`](FreeCamera::nudge_step) along world +Y.
+    NudgeUp,
+    /// Nudge by [`nudge_step`](FreeCamera::nudge_step) along world -Y.
+    NudgeDown,
+    /// Pitch up at [`keyboard_look_speed`](FreeCamera::keyboard_look_speed) while held.
+    LookUp,
+    /// Pitch down at [`keyboard_look_speed`](FreeCamera::keyboard_look_speed) while held.
+    LookDown,
+    /// Turn left at [`keyboard_look_speed`](FreeCamera::keyboard_look_speed) while held.
+    LookLeft,
+    /// Turn right at [`keyboard_look_speed`](FreeCamera::keyboard_look_speed) while held.
+    LookRight,
+    /// Move back to [`last_grounded_position`](FreeCameraState::last_grounded_position).
+    ReturnToGround,
+}
+
+impl Action {
+    /// The actions that translate the camera.
+    pub const MOVEMENT: [Action; 6] = [
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+    ];
+}
+
+/// A physical input an action can be bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputBinding {
+    /// A keyboard key.
+    Key(KeyCode),
+    /// A mouse button.
+    Mouse(MouseButton),
+    /// A button on any connected gamepad.
+    Gamepad(GamepadButton),
+}
+
+impl From<KeyCode> for InputBinding {
+    fn from(key: KeyCode) -> Self {
+        InputBinding::Key(key)
+    }
+}
+
+impl From<MouseButton> for InputBinding {
+    fn from(button: MouseButton) -> Self {
+        InputBinding::Mouse(button)
+    }
+}
+
+impl From<GamepadButton> for InputBinding {
+    fn from(button: GamepadButton) -> Self {
+        InputBinding::Gamepad(button)
+    }
+}
+
+impl fmt::Display for InputBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputBinding::Key(key) => write!(f, "{key:?}"),
+            InputBinding::Mouse(button) => write!(f, "{button:?}"),
+            InputBinding::Gamepad(button) => write!(f, "{button:?}"),
+        }
+    }
+}
+
+/// Maps actions to the inputs that trigger them. An action can have any number of bindings
+/// and is active while any one of them is.
+///
+/// The [`FreeCameraPlugin`] reads its controls from the `InputMap<Action>` resource, whose
+/// default is the standard keyboard and mouse layout.
+#[derive(Resource, Clone, Debug)]
+pub struct InputMap<A> {
+    bindings: HashMap<A, Vec<InputBinding>>,
+}
+
+impl<A: Copy + Eq + Hash> InputMap<A> {
+    /// Creates a map without any bindings.
+    pub fn empty() -> Self {
+        Self {
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// Adds `binding` to `action`, keeping its existing bindings.
+    pub fn bind(&mut self, action: A, binding: impl Into<InputBinding>) -> &mut Self {
+        let binding = binding.into();
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self
+    }
+
+    /// Returns the inputs bound to `action`.
+    pub fn bindings(&self, action: A) -> &[InputBinding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the bindings of `action` joined for display, e.g. in a help text.
+    pub fn describe(&self, action: A) -> String {
+        let bindings = self.bindings(action);
+        if bindings.is_empty() {
+            return "Unbound".to_string();
+        }
+        bindings
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+impl Default for InputMap<Action> {
+    fn default() -> Self {
+        let mut map = Self::empty();
+        map.bind(Action::MoveForward, KeyCode::KeyW)
+            .bind(Action::MoveBack, KeyCode::KeyS)
+            .bind(Action::MoveLeft, KeyCode::KeyA)
+            .bind(Action::MoveRight, KeyCode::KeyD)
+            .bind(Action::MoveUp, KeyCode::KeyE)
+            .bind(Action::MoveDown, KeyCode::KeyQ)
+            .bind(Action::Run, KeyCode::ShiftLeft)
+            .bind(Action::Crouch, KeyCode::ControlLeft)
+            .bind(Action::SnapLeft, KeyCode::Comma)
+            .bind(Action::SnapRight, KeyCode::Period)
+            .bind(Action::ScrollFov, KeyCode::ShiftLeft)
+            .bind(Action::ScrollNear, KeyCode::ControlLeft)
+            .bind(Action::GrabCursor, MouseButton::Left)
+            .bind(Action::ToggleCursorGrab, KeyCode::KeyM)
+            .bind(Action::Orbit, MouseButton::Right)
+            .bind(Action::Pan, MouseButton::Middle)
+            .bind(Action::NudgeLeft, KeyCode::Numpad4)
+            .bind(Action::NudgeRight, KeyCode::Numpad6)
+            .bind(Action::NudgeForward, KeyCode::Numpad8)
+            .bind(Action::NudgeBack, KeyCode::Numpad2)
+            .bind(Action::NudgeUp, KeyCode::PageUp)
+            .bind(Action::NudgeDown, KeyCode::PageDown)
+            .bind(Action::LookUp, KeyCode::ArrowUp)
+            .bind(Action::LookDown, KeyCode::ArrowDown)
+            .bind(Action::LookLeft, KeyCode::ArrowLeft)
+            .bind(Action::LookRight, KeyCode::ArrowRight)
+            .bind(Action::ReturnToGround, KeyCode::NumpadDecimal);
+        map
+    }
+}
+
+impl InputMap<Action> {
+    /// Formats the help lines for looking around and grabbing the cursor.
+    pub fn fmt_look_controls(&self) -> String {
+        format!(
+            "    Mouse\t- Move camera orientation
+    {} {} {} {}\t- Look up, down, left & right without a mouse
+    Right stick\t- Look around with a gamepad
+    Scroll\t- Adjust movement speed
+    {} + Scroll\t- Adjust field of view
+    {} + Scroll\t- Adjust near plane
+    {}\t- Hold to grab cursor
+    {}\t- Toggle cursor grab",
+            self.describe(Action::LookUp),
+            self.describe(Action::LookDown),
+            self.describe(Action::LookLeft),
+            self.describe(Action::LookRight),
+            self.describe(Action::ScrollFov),
+            self.describe(Action::ScrollNear),
+            self.describe(Action::GrabCursor),
+            self.describe(Action::ToggleCursorGrab),
+        )
+    }
+
+    /// Formats the help lines for flying, running and crouching.
+    pub fn fmt_move_controls(&self) -> String {
+        format!(
+            "    {} & {}\t- Fly forward & backwards
+    {} & {}\t- Fly sideways left & right
+    {} & {}\t- Fly up & down
+    {}\t- Fly faster while held
+    {}\t- Crouch while held",
+            self.describe(Action::MoveForward),
+            self.describe(Action::MoveBack),
+            self.describe(Action::MoveLeft),
+            self.describe(Action::MoveRight),
+            self.describe(Action::MoveUp),
+            self.describe(Action::MoveDown),
+            self.describe(Action::Run),
+            self.describe(Action::Crouch),
+        )
+    }
+
+    /// Formats the help lines for snap turns, nudges and returning to the ground.
+    pub fn fmt_step_controls(&self) -> String {
+        format!(
+            "    {} & {}\t- Snap turn left & right
+    {} & {}, {} & {}, {} & {}\t- Nudge along world axes
+    {}\t- Return to the last ground walked on",
+            self.describe(Action::SnapLeft),
+            self.describe(Action::SnapRight),
+            self.describe(Action::NudgeLeft),
+            self.describe(Action::NudgeRight),
+            self.describe(Action::NudgeForward),
+            self.describe(Action::NudgeBack),
+            self.describe(Action::NudgeUp),
+            self.describe(Action::NudgeDown),
+            self.describe(Action::ReturnToGround),
+        )
+    }
+}
+
+impl fmt::Display for InputMap<Action> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "\nFreecam Controls:\n{}\n{}\n{}",
+            self.fmt_look_controls(),
+            self.fmt_move_controls(),
+            self.fmt_step_controls(),
+        )
+    }
+}
+
+/// Turns the mouse motion accumulated over a frame into look input, measured in dots.
+///
+/// Native platforms report raw device motion, which doesn't depend on the display. On the web
+/// the motion comes from the Pointer Lock API's `movementX`/`movementY`, which browsers report
+/// in CSS pixels and winit scales by the device pixel ratio, so the same hand movement turns
+/// the camera further on a high-DPI screen. [`PlatformMotionSource`] picks the right source for
+/// the target at build time.
+pub trait MouseMotionSource {
+    /// Whether this source reads pointer lock movement rather than raw device motion.
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    const POINTER_LOCK: bool;
+
+    /// Converts `motion`, accumulated from mouse motion events, for a window with the given
+    /// scale factor.
+    fn look_delta(motion: Vec2, scale_factor: f32) -> Vec2;
+}
+
+/// Raw device motion, used on native platforms.
+pub struct RawMouseMotion;
+
+impl MouseMotionSource for RawMouseMotion {
+    const POINTER_LOCK: bool = false;
+
+    fn look_delta(motion: Vec2, _scale_factor: f32) -> Vec2 {
+        motion
+    }
+}
+
+/// Pointer lock movement, used on the web. Undoes the device pixel ratio so look speed
+/// doesn't depend on the screen density.
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+pub struct PointerLockMotion;
+
+impl MouseMotionSource for PointerLockMotion {
+    const POINTER_LOCK: bool = true;
+
+    fn look_delta(motion: Vec2, scale_factor: f32) -> Vec2 {
+        if scale_factor > 0.0 {
+            motion / scale_factor
+        } else {
+            motion
+        }
+    }
+}
+
+/// The [`MouseMotionSource`] the controller uses on the current target.
+#[cfg(not(target_arch = "wasm32"))]
+pub type PlatformMotionSource = RawMouseMotion;
+/// The [`MouseMotionSource`] the controller uses on the current target.
+#[cfg(target_arch = "wasm32")]
+pub type PlatformMotionSource = PointerLockMotion;
+
+// Browsers only report relative motion through pointer lock
+#[cfg(target_arch = "wasm32")]
+const _: () = assert!(<PlatformMotionSource as MouseMotionSource>::POINTER_LOCK);
+
+/// Reads the state of actions from the keyboard, mouse and gamepads through the
+/// [`InputMap`] for `A`.
+#[derive(SystemParam)]
+pub struct ActionInput<'w, 's, A: Copy + Eq + Hash + Send + Sync + 'static> {
+    input_map: Res<'w, InputMap<A>>,
+    key_input: Res<'w, ButtonInput<KeyCode>>,
+    mouse_button_input: Res<'w, ButtonInput<MouseButton>>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl<A: Copy + Eq + Hash + Send + Sync + 'static> ActionInput<'_, '_, A> {
+    /// Returns the bindings the actions are read through.
+    pub fn input_map(&self) -> &InputMap<A> {
+        &self.input_map
+    }
+
+    /// Returns `true` while any input bound to `action` is held.
+    pub fn pressed(&self, action: A) -> bool {
+        self.any(action, |binding| match binding {
+            InputBinding::Key(key) => self.key_input.pressed(key),
+            InputBinding::Mouse(button) => self.mouse_button_input.pressed(button),
+            InputBinding::Gamepad(button) => self.gamepads.iter().any(|pad| pad.pressed(button)),
+        })
+    }
+
+    /// Returns `true` if any input bound to `action` was pressed this frame.
+    pub fn just_pressed(&self, action: A) -> bool {
+        self.any(action, |binding| match binding {
+            InputBinding::Key(key) => self.key_input.just_pressed(key),
+            InputBinding::Mouse(button) => self.mouse_button_input.just_pressed(button),
+            InputBinding::Gamepad(button) => {
+                self.gamepads.iter().any(|pad| pad.just_pressed(button))
+            }
+        })
+    }
+
+    /// Returns `true` if any input bound to `action` was released this frame.
+    pub fn just_released(&self, action: A) -> bool {
+        self.any(action, |binding| match binding {
+            InputBinding::Key(key) => self.key_input.just_released(key),
+            InputBinding::Mouse(button) => self.mouse_button_input.just_released(button),
+            InputBinding::Gamepad(button) => {
+                self.gamepads.iter().any(|pad| pad.just_released(button))
+            }
+        })
+    }
+
+    /// Returns the right stick position summed over all connected gamepads, each axis clamped
+    /// to `[-1, 1]`.
+    pub fn right_stick(&self) -> Vec2 {
+        self.gamepads
+            .iter()
+            .map(Gamepad::right_stick)
+            .sum::<Vec2>()
+            .clamp(Vec2::NEG_ONE, Vec2::ONE)
+    }
+
+    fn any(&self, action: A, active: impl Fn(InputBinding) -> bool) -> bool {
+        self.input_map
+            .bindings(action)
+            .iter()
+            .any(|binding| active(*binding))
+    }
+}
+
+/// Marks an entity that the [`FreeCamera`] cannot move through.
+///
+/// The collision shape is the entity's [`Aabb`] transformed into world space, so any mesh
+/// can be made solid by adding this component. Collision is only resolved when
+/// [`FreeCamera::collisions`] is `true`, and only against colliders whose
+/// [`layers`](CameraCollider::layers) intersect [`FreeCamera::collision_mask`].
+#[derive(Component, Clone, Copy, Default)]
+pub struct CameraCollider {
+    /// Layers this collider is on.
+    pub layers: CollisionLayer,
+}
+
+impl CameraCollider {
+    /// Returns `true` if this collider stops a camera with the given settings.
+    pub fn blocks(&self, config: &FreeCamera) -> bool {
+        config.collisions && self.layers.intersects(config.collision_mask)
+    }
+}
+
+/// A set of collision layers, one bit per layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CollisionLayer(pub u32);
+
+impl CollisionLayer {
+    /// The layer colliders are on unless told otherwise.
+    pub const DEFAULT: Self = Self(1);
+    /// Every layer.
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// Returns `true` if the two sets share at least one layer.
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl Default for CollisionLayer {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Keeps a [`FreeCamera`] aimed at another entity, taking over from mouse look.
+///
+/// When the target changes, the camera turns towards the new one over
+/// [`blend_duration`](LookAtTarget::blend_duration) rather than snapping, following the
+/// target's live position if it moves in the meantime. Mouse look picks up from wherever the
+/// camera was aimed once the component is removed.
+#[derive(Component, Clone, Copy)]
+pub struct LookAtTarget {
+    /// The entity to look at.
+    pub entity: Entity,
+    /// Seconds it takes to turn towards a new target. `0.0` snaps straight to it.
+    pub blend_duration: f32,
+    /// Turn towards [`entity`](LookAtTarget::entity) in progress.
+    blend: Option<LookAtBlend>,
+}
+
+impl LookAtTarget {
+    /// Looks at `entity`, turning towards it over half a second.
+    pub fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            blend_duration: 0.5,
+            blend: None,
+        }
+    }
+}
+
+/// Keeps an orbiting [`FreeCamera`] from clipping into colliders between it and its focus.
+///
+/// Every update a ray is cast from the focus back towards where the camera would be at
+/// [`FreeCameraState::focus_distance`], and the camera is pulled in to just in front of the
+/// first [`CameraCollider`] hit. It springs back out once nothing is in the way. Only the
+/// orbiting [`ControlScheme`]s use it, and only colliders the camera collides with count.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct SpringArm {
+    /// Current distance from the focus to the camera, at most
+    /// [`FreeCameraState::focus_distance`]. Set by the controller.
+    pub length: f32,
+    /// Shortest the arm is pulled in to, even if a collider is closer to the focus.
+    pub min_length: f32,
+}
+
+impl SpringArm {
+    /// A spring arm that pulls the camera no closer than `min_length` to the focus.
+    pub fn new(min_length: f32) -> Self {
+        Self {
+            length: f32::INFINITY,
+            min_length,
+        }
+    }
+
+    /// Returns the arm length that keeps a camera with `collision_radius` in front of `hit`,
+    /// the first collider along the arm from the focus, up to `focus_distance`.
+    pub fn fit_length(
+        &self,
+        focus_distance: f32,
+        hit: Option<RayHit>,
+        collision_radius: f32,
+    ) -> f32 {
+        hit.filter(|hit| hit.distance < focus_distance + collision_radius)
+            .map_or(focus_distance, |hit| {
+                (hit.distance - collision_radius).max(self.min_length)
+            })
+            .min(focus_distance)
+    }
+}
+
+impl Default for SpringArm {
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+
+/// A turn from the rotation a camera had when its [`LookAtTarget`] changed.
+#[derive(Clone, Copy)]
+struct LookAtBlend {
+    entity: Entity,
+    from: Quat,
+    elapsed: f32,
+}
+
+/// Smooths out the rendered [`Transform`] of an entity that is moved in [`FixedUpdate`].
+///
+/// While the fixed update systems run, the entity's [`Transform`] holds the simulated state.
+/// Before rendering it is set to a blend of the last two fixed ticks based on the overstep
+/// fraction of [`Time<Fixed>`], so motion stays smooth when the frame rate is not a multiple
+/// of the tick rate. Changes made to the [`Transform`] outside of fixed update, such as
+/// teleports, are taken as the new simulated state.
+#[derive(Component, Clone, Copy, Default)]
+pub struct PreviousTransform {
+    /// The simulated transform at the end of the previous fixed tick.
+    pub transform: Transform,
+    /// The simulated transform at the end of the latest fixed tick.
+    current: Transform,
+    /// The transform last written by the fixed timestep systems, used to spot outside changes.
+    written: Transform,
+}
+
+impl PreviousTransform {
+    /// Adopts `transform` as the simulated state if something else has changed it.
+    fn sync(&mut self, transform: &Transform) {
+        if *transform != self.written {
+            self.transform = *transform;
+            self.current = *transform;
+            self.written = *transform;
+        }
+    }
+}
+
+/// Blends between two transforms, `t` being `0.0` at `previous` and `1.0` at `current`.
+///
+/// Translation and scale are interpolated linearly and rotation spherically.
+pub fn interpolate_transform(previous: &Transform, current: &Transform, t: f32) -> Transform {
+    Transform {
+        translation: previous.translation.lerp(current.translation, t),
+        rotation: previous.rotation.slerp(current.rotation, t),
+        scale: previous.scale.lerp(current.scale, t),
+    }
+}
+
+/// Stores the settings for the [`FreeCamera`] controller.
+///
+/// This component defines static configuration for camera controls,
+/// while [`FreeCameraState`] manages the dynamic runtime state.
+#[derive(Component)]
+#[require(FreeCameraState)]
+pub struct FreeCamera {
+    /// Multiplier for pitch and yaw rotation speed.
+    pub sensitivity: f32,
+    /// Treats pointer motion as coming from a trackpad rather than a mouse, which report very
+    /// different amounts of motion for a comfortable gesture. Platforms don't reliably say
+    /// which device moved, so this is set by hand.
+    pub trackpad_mode: bool,
+    /// Scale applied to mouse look and panning when [`trackpad_mode`](FreeCamera::trackpad_mode)
+    /// is off.
+    pub mouse_sensitivity_scale: f32,
+    /// Scale applied to mouse look and panning when [`trackpad_mode`](FreeCamera::trackpad_mode)
+    /// is on.
+    pub trackpad_sensitivity_scale: f32,
+    /// Largest mouse motion, in dots, applied to the look rotation in a single frame.
+    ///
+    /// Anything above this is clamped, which keeps a single spurious delta (e.g. from a
+    /// stalled frame or the cursor being warped) from whipping the view around.
+    /// Set to [`f32::INFINITY`] to disable the clamp.
+    pub max_look_delta: f32,
+    /// Largest combined yaw and pitch change, in radians, mouse look can apply in a single
+    /// frame.
+    ///
+    /// Unlike [`max_look_delta`](FreeCamera::max_look_delta) this holds after
+    /// [`sensitivity`](FreeCamera::sensitivity) is applied, so a laggy frame at a high
+    /// sensitivity can't spin the camera around. Set to [`f32::INFINITY`] to disable the clamp.
+    pub max_rotation_per_frame: f32,
+    /// Turn rate, in radians per second, of the [`Action::LookUp`], [`Action::LookDown`],
+    /// [`Action::LookLeft`] and [`Action::LookRight`] keys.
+    ///
+    /// Keyboard look works without grabbing the cursor and adds to any mouse look.
+    pub keyboard_look_speed: f32,
+    /// Turn rate, in radians per second, with the right gamepad stick fully deflected.
+    ///
+    /// Like keyboard look, stick look works without grabbing the cursor and adds to any
+    /// mouse look.
+    pub gamepad_look_speed: f32,
+    /// Share of the right stick's travel, from the center, that is ignored so a stick that
+    /// doesn't quite center doesn't drift the view.
+    pub gamepad_look_deadzone: f32,
+    /// Exponent of the right stick response curve, applied to the deflection past the
+    /// deadzone.
+    ///
+    /// `1.0` is linear, higher values give finer control near the center while keeping full
+    /// deflection as fast.
+    pub gamepad_look_exponent: f32,
+    /// Distance panned per dot of mouse motion, relative to the distance to the focus.
+    ///
+    /// Scaling with the focus distance keeps the point under the cursor moving at roughly the
+    /// same speed as the cursor, however far away it is.
+    pub pan_speed: f32,
+    /// Which set of controls the camera responds to.
+    pub control_scheme: ControlScheme,
+    /// What happens to the cursor while it is grabbed. Changes apply right away, also while
+    /// the cursor is grabbed.
+    pub cursor_mode: CursorMode,
+    /// Whether the cursor can be grabbed for looking around.
+    ///
+    /// Turning it off releases a grabbed cursor and ignores the grab actions, so the mouse
+    /// doesn't turn the camera, e.g. while a UI needs the cursor. Turning it back on grabs the
+    /// cursor again if it was grabbed before.
+    pub cursor_grab_enabled: bool,
+    /// Distance from the camera to the focus point it turns around in the orbiting
+    /// [`ControlScheme`]s, when the controller starts.
+    pub orbit_distance: f32,
+    /// Distance the camera is nudged along a world axis per press of a nudge [`Action`].
+    ///
+    /// Holding [`Action::Run`] nudges ten times as far.
+    pub nudge_step: f32,
+    /// Translation speed, in units per second, at a
+    /// [`speed_multiplier`](FreeCameraState::speed_multiplier) of `1.0`.
+    ///
+    /// The speed the camera actually moves at is this times the speed multiplier, see
+    /// [`effective_speeds`](FreeCamera::effective_speeds).
+    pub walk_speed: f32,
+    /// Like [`walk_speed`](FreeCamera::walk_speed), but while [`Action::Run`] is held.
+    pub run_speed: f32,
+    /// Change of the [`speed_multiplier`](FreeCameraState::speed_multiplier) per scroll wheel
+    /// notch, or per step of [`adjust_speed`](FreeCameraState::adjust_speed).
+    ///
+    /// With the default of `0.5`, two notches up double the walk and run speeds.
+    pub scroll_factor: f32,
+    /// Reverses the scroll wheel direction for everything it adjusts, e.g. for trackpads with
+    /// natural scrolling.
+    pub invert_scroll: bool,
+    /// Time constant, in seconds, over which the speed eases towards the value set by
+    /// scrolling. `0.0` applies each scroll step instantly.
+    pub scroll_smoothing: f32,
+    /// Field of view change, in radians, per line scrolled while [`Action::ScrollFov`] is held.
+    pub scroll_fov_step: f32,
+    /// Fraction the near clipping plane grows or shrinks by per line scrolled while
+    /// [`Action::ScrollNear`] is held.
+    pub scroll_near_factor: f32,
+    /// Friction factor used to exponentially decay [`velocity`](FreeCameraState::velocity) over time.
+    pub friction: f32,
+    /// Whether the camera glides to a stop under [`friction`](FreeCamera::friction) once the
+    /// movement keys are released.
+    ///
+    /// When `false` the velocity follows the keys exactly, stopping the same update they are
+    /// released and steering fully in the air. Gravity still applies in
+    /// [`MovementMode::Walk`].
+    pub inertia: bool,
+    /// Friction used instead of [`friction`](FreeCamera::friction) while airborne in
+    /// [`MovementMode::Walk`], usually lower so jumps carry their momentum.
+    pub air_friction: f32,
+    /// Seconds it takes movement to fade out after [`FreeCameraState::enabled`] is cleared, and
+    /// for input to fade back in once it is set again.
+    ///
+    /// `0.0` stops and starts the controller instantly.
+    pub enable_transition: f32,
+    /// Highest world Y the camera can reach, e.g. to stay below a ceiling. `None` leaves it
+    /// unbounded.
+    pub max_height: Option<f32>,
+    /// Lowest world Y the camera can reach, e.g. to stay above a floor. `None` leaves it
+    /// unbounded.
+    ///
+    /// In [`MovementMode::Walk`] this holds the feet instead, so the camera stays
+    /// [`eye_height`](FreeCameraState::eye_height) above it.
+    pub min_height: Option<f32>,
+    /// World Y below which a walking camera is considered lost, e.g. after falling through
+    /// the floor, and is moved back to
+    /// [`last_grounded_position`](FreeCameraState::last_grounded_position). `None` disables it.
+    pub kill_plane: Option<f32>,
+    /// Upper bound on the length of [`velocity`](FreeCameraState::velocity), applied every frame
+    /// after the velocity is updated.
+    ///
+    /// Guards against runaway speeds when several speed boosts stack up. `None` leaves the
+    /// velocity unbounded.
+    pub max_speed: Option<f32>,
+    /// Scales the movement speed by the distance to the nearest [`CameraCollider`], slowing
+    /// down in tight spaces and speeding up in the open.
+    pub proximity_speed_scaling: bool,
+    /// Distance to the nearest collider at which proximity scaling leaves the speed unchanged.
+    /// The speed is scaled by the actual distance divided by this one.
+    pub proximity_reference_distance: f32,
+    /// Smallest factor proximity scaling slows the camera down by.
+    pub proximity_speed_min: f32,
+    /// Largest factor proximity scaling speeds the camera up by, also used when there is no
+    /// collider at all.
+    pub proximity_speed_max: f32,
+    /// Field of view, in radians, added to the perspective projection while running.
+    ///
+    /// The kick is applied as an offset on top of whatever the field of view currently is,
+    /// so other systems can keep adjusting the base value. `0.0` disables it.
+    pub sprint_fov_delta: f32,
+    /// Decay rate used to ease the field of view towards or away from the sprint kick.
+    pub sprint_fov_speed: f32,
+    /// Yaw angle, in radians, that the snap keys turn by and align to.
+    pub snap_increment: f32,
+    /// Time, in seconds, a snap turn takes to complete.
+    pub snap_duration: f32,
+    /// Time, in seconds, [`level_out`](FreeCameraState::level_out) takes to complete.
+    pub level_duration: f32,
+    /// Curve used to ease snap turns, leveling out and [`LookAtTarget`] blends.
+    pub look_ease: EaseKind,
+    /// Curve shaping the crouch and the sprint field of view kick.
+    ///
+    /// Both move their progress towards the target at a decay rate, and this curve maps that
+    /// progress to the eye height or field of view offset.
+    pub transition_ease: EaseKind,
+    /// Eye height while standing.
+    pub stand_height: f32,
+    /// Eye height while fully crouched.
+    ///
+    /// Only the difference to [`stand_height`](FreeCamera::stand_height) is used, the camera
+    /// is lowered by that amount when crouching.
+    pub crouch_height: f32,
+    /// Multiplier applied to the walking speed while fully crouched.
+    pub crouch_speed_multiplier: f32,
+    /// Fraction of the walking speed lost while looking straight up or down.
+    ///
+    /// The loss grows with the square of the pitch angle, so looking slightly up or down
+    /// barely changes the speed. Only applies in [`MovementMode::Walk`]. `0.0` disables it.
+    pub pitch_speed_falloff: f32,
+    /// Decay rate used to ease the eye height between standing and crouching.
+    pub crouch_transition_speed: f32,
+    /// Whether to fly freely or walk on the ground.
+    pub movement_mode: MovementMode,
+    /// How the movement keys map to world space directions.
+    pub movement_basis: MovementBasis,
+    /// Move along [`up`](FreeCamera::up) for [`Action::MoveUp`] and [`Action::MoveDown`] in
+    /// [`MovementMode::Fly`], regardless of pitch.
+    ///
+    /// When `false` they follow the camera's own up axis instead, so looking down and pressing
+    /// up moves forward and up. Defaults to `true`, which is how vertical movement has always
+    /// behaved. Rebind the keys through [`InputMap`], e.g. to Space and C.
+    pub fly_vertical_world_up: bool,
+    /// Direction the camera treats as up.
+    ///
+    /// Yaw turns around this axis, pitch is measured from the plane perpendicular to it, and
+    /// vertical movement, jumping and gravity follow it. Collision boxes stay aligned with the
+    /// world axes though, so [`MovementMode::Walk`] only finds the ground when this is
+    /// [`Vec3::Y`].
+    pub up: Vec3,
+    /// Collide with [`CameraCollider`] entities instead of passing through them.
+    pub collisions: bool,
+    /// Layers the camera collides with, [`CameraCollider`]s on any other layer are passed
+    /// through.
+    pub collision_mask: CollisionLayer,
+    /// Horizontal half-size of the camera body used for collision.
+    pub collision_radius: f32,
+    /// Seconds over which the speed kept when running into a wall at an angle eases down to
+    /// the plain slide along it.
+    ///
+    /// Moving into a wall only keeps the part of the movement along it, so hitting one at a
+    /// steep angle slows the camera down abruptly. With smoothing the camera starts sliding
+    /// at its full speed instead. `0.0` projects the movement onto the wall straight away.
+    pub slide_smoothing: f32,
+    /// Tallest ledge, measured from the feet, that is climbed automatically in
+    /// [`MovementMode::Walk`] instead of blocking movement.
+    pub step_height: f32,
+    /// Downward acceleration applied in [`MovementMode::Walk`].
+    pub gravity: f32,
+    /// Upward speed given by a jump in [`MovementMode::Walk`].
+    pub jump_speed: f32,
+    /// Highest speed the camera falls at in [`MovementMode::Walk`], however long the fall.
+    pub terminal_velocity: f32,
+    /// How much the movement keys steer while airborne in [`MovementMode::Walk`].
+    ///
+    /// This is the fraction of the gap between the current and the steered horizontal velocity
+    /// that is closed every 60th of a second. `1.0` steers like on the ground, `0.0` keeps the
+    /// momentum the camera left the ground with.
+    pub air_control: f32,
+    /// How long a press of a movement key keeps counting as held, in milliseconds.
+    ///
+    /// A key tapped between two controller updates is otherwise never seen held, which
+    /// happens at high frame rates, and in frames without a tick with
+    /// [`run_in_fixed_update`](FreeCameraPlugin::run_in_fixed_update). `0.0` only counts keys
+    /// that are down during the update.
+    pub input_buffer_ms: f32,
+}
+
+impl Default for FreeCamera {
+    fn default() -> Self {
+        Self {
+            sensitivity: 0.2,
+            trackpad_mode: false,
+            mouse_sensitivity_scale: 1.0,
+            trackpad_sensitivity_scale: 2.0,
+            max_look_delta: 500.0,
+            max_rotation_per_frame: FRAC_PI_2,
+            keyboard_look_speed: FRAC_PI_2,
+            gamepad_look_speed: PI,
+            gamepad_look_deadzone: 0.1,
+            gamepad_look_exponent: 1.5,
+            pan_speed: 0.002,
+            control_scheme: ControlScheme::Fps,
+            cursor_mode: CursorMode::GrabbedHidden,
+            cursor_grab_enabled: true,
+            orbit_distance: 10.0,
+            nudge_step: 0.05,
+            walk_speed: 5.0,
+            run_speed: 15.0,
+            scroll_factor: 0.5,
+            invert_scroll: false,
+            scroll_smoothing: 0.0,
+            scroll_fov_step: 2f32.to_radians(),
+            scroll_near_factor: 0.1,
+            enable_transition: 0.0,
+            friction: 40.0,
+            inertia: true,
+            air_friction: 1.0,
+            max_height: None,
+            min_height: None,
+            kill_plane: None,
+            max_speed: None,
+            proximity_speed_scaling: false,
+            proximity_reference_distance: 5.0,
+            proximity_speed_min: 0.2,
+            proximity_speed_max: 4.0,
+            sprint_fov_delta: 0.0,
+            sprint_fov_speed: 8.0,
+            snap_increment: FRAC_PI_4,
+            snap_duration: 0.15,
+            level_duration: 0.3,
+            look_ease: EaseKind::Smoothstep,
+            transition_ease: EaseKind::Smoothstep,
+            stand_height: 1.7,
+            crouch_height: 1.0,
+            crouch_speed_multiplier: 0.5,
+            pitch_speed_falloff: 0.0,
+            crouch_transition_speed: 12.0,
+            movement_mode: MovementMode::Fly,
+            movement_basis: MovementBasis::CameraRelative,
+            fly_vertical_world_up: true,
+            up: Vec3::Y,
+            collisions: true,
+            collision_mask: CollisionLayer::ALL,
+            collision_radius: 0.25,
+            slide_smoothing: 0.0,
+            step_height: 0.4,
+            gravity: 9.81,
+            jump_speed: 4.0,
+            terminal_velocity: 53.0,
+            air_control: 0.05,
+            input_buffer_ms: 0.0,
+        }
+    }
+}
+
+impl FreeCamera {
+    /// Returns the rotation that takes world Y onto [`up`](FreeCamera::up).
+    ///
+    /// Falls back to no rotation if `up` is zero or not finite.
+    pub fn up_rotation(&self) -> Quat {
+        Quat::from_rotation_arc(Vec3::Y, self.up.try_normalize().unwrap_or(Vec3::Y))
+    }
+
+    /// Returns the height of the camera above the feet while `crouch` of the way into a
+    /// crouch, see [`FreeCameraState::eye_height`].
+    pub fn eye_height(&self, crouch: f32) -> f32 {
+        self.stand_height
+            .lerp(self.crouch_height, apply_ease(self.transition_ease, crouch))
+    }
+
+    /// Limits the camera `height` so the feet, `eye_height` below it, stay above
+    /// [`min_height`](FreeCamera::min_height) and the camera stays below
+    /// [`max_height`](FreeCamera::max_height).
+    ///
+    /// The ceiling wins if the two bounds cross.
+    pub fn clamp_height(&self, height: f32, eye_height: f32) -> f32 {
+        let height = self
+            .min_height
+            .map_or(height, |min| height.max(min + eye_height));
+        self.max_height.map_or(height, |max| height.min(max))
+    }
+
+    /// Returns the speed factor [`pitch_speed_falloff`](FreeCamera::pitch_speed_falloff)
+    /// applies at the given pitch, from `1.0` when looking level.
+    pub fn pitch_speed_factor(&self, pitch: f32) -> f32 {
+        let steepness = (pitch.abs() / FRAC_PI_2).min(1.0);
+        1.0 - self.pitch_speed_falloff.clamp(0.0, 1.0) * steepness * steepness
+    }
+
+    /// Limits one update's mouse motion to [`max_look_delta`](FreeCamera::max_look_delta)
+    /// dots, keeping its direction.
+    pub fn clamp_look_delta(&self, delta: Vec2) -> Vec2 {
+        delta.clamp_length_max(self.max_look_delta.max(0.0))
+    }
+
+    /// Returns the yaw and pitch change in radians for `delta` dots of mouse motion, limited to
+    /// [`max_rotation_per_frame`](FreeCamera::max_rotation_per_frame).
+    pub fn mouse_turn(&self, delta: Vec2) -> Vec2 {
+        (delta * RADIANS_PER_DOT * self.sensitivity)
+            .clamp_length_max(self.max_rotation_per_frame.max(0.0))
+    }
+
+    /// Applies [`gamepad_look_deadzone`](FreeCamera::gamepad_look_deadzone) and
+    /// [`gamepad_look_exponent`](FreeCamera::gamepad_look_exponent) to a stick position.
+    ///
+    /// The result keeps the stick's direction, with a length from `0.0` at the edge of the
+    /// deadzone to `1.0` at full deflection.
+    pub fn shape_gamepad_look(&self, stick: Vec2) -> Vec2 {
+        let deadzone = self.gamepad_look_deadzone.clamp(0.0, 0.99);
+        let length = stick.length();
+        if length <= deadzone {
+            return Vec2::ZERO;
+        }
+        let deflection = ((length - deadzone) / (1.0 - deadzone)).min(1.0);
+        stick / length * deflection.powf(self.gamepad_look_exponent.max(0.0))
+    }
+
+    /// Returns the sensitivity scale for the pointing device in use, see
+    /// [`trackpad_mode`](FreeCamera::trackpad_mode).
+    pub fn device_sensitivity_scale(&self) -> f32 {
+        if self.trackpad_mode {
+            self.trackpad_sensitivity_scale
+        } else {
+            self.mouse_sensitivity_scale
+        }
+    }
+
+    /// Returns the camera orientation for the given yaw and pitch, relative to
+    /// [`up`](FreeCamera::up).
+    pub fn look_rotation(&self, yaw: f32, pitch: f32) -> Quat {
+        self.up_rotation() * Quat::from_euler(EulerRot::ZYX, 0.0, yaw, pitch)
+    }
+
+    /// Returns the world space right, up and forward axes the movement keys map to for a
+    /// camera with the given `rotation` and `yaw`.
+    ///
+    /// Walking always moves along [`MovementBasis::YawRelative`] axes and jumps along
+    /// [`up`](FreeCamera::up), so looking down doesn't push the camera into the ground.
+    pub fn movement_frame(&self, rotation: Quat, yaw: f32, walking: bool) -> (Vec3, Vec3, Vec3) {
+        let basis = match self.movement_basis {
+            MovementBasis::CameraRelative if walking => MovementBasis::YawRelative,
+            basis => basis,
+        };
+        let up_rotation = self.up_rotation();
+        let (forward, right) = basis.axes(rotation, yaw, up_rotation);
+        let up = if self.fly_vertical_world_up || walking {
+            up_rotation * Vec3::Y
+        } else {
+            rotation * Vec3::Y
+        };
+        (right, up, forward)
+    }
+
+    /// Formats the control scheme and movement mode, one per line.
+    pub fn fmt_controls(&self) -> String {
+        format!(
+            "Scheme: {:?}\nMode: {:?}",
+            self.control_scheme, self.movement_mode
+        )
+    }
+
+    /// Formats the mouse look sensitivity and the pointing device it is tuned for.
+    pub fn fmt_look(&self) -> String {
+        let device = if self.trackpad_mode {
+            "trackpad"
+        } else {
+            "mouse"
+        };
+        format!(
+            "Sensitivity: {:.03} ({device} x{:.02})",
+            self.sensitivity,
+            self.device_sensitivity_scale()
+        )
+    }
+
+    /// Formats the friction and whether inertia is on.
+    pub fn fmt_friction(&self) -> String {
+        format!("Friction: {:.01}, inertia: {}", self.friction, self.inertia)
+    }
+
+    /// Formats the scroll factor and whether scrolling is inverted, one per line.
+    pub fn fmt_scroll(&self) -> String {
+        format!(
+            "Scroll factor: {:.02}\nInvert scroll: {}",
+            self.scroll_factor, self.invert_scroll
+        )
+    }
+
+    /// Returns the walk and run speeds after the
+    /// [`speed_multiplier`](FreeCameraState::speed_multiplier) of `state`.
+    ///
+    /// Crouching, proximity scaling and pitch falloff slow the camera further while moving.
+    pub fn effective_speeds(&self, state: &FreeCameraState) -> (f32, f32) {
+        (
+            self.walk_speed * state.speed_multiplier,
+            self.run_speed * state.speed_multiplier,
+        )
+    }
+
+    /// Formats the base and effective walk and run speeds, one per line.
+    pub fn fmt_speeds(&self, state: &FreeCameraState) -> String {
+        let (walk, run) = self.effective_speeds(state);
+        format!(
+            "Walk Speed: {:.02} x{:.02} = {walk:.02}\nRun Speed: {:.02} x{:.02} = {run:.02}",
+            self.walk_speed, state.speed_multiplier, self.run_speed, state.speed_multiplier
+        )
+    }
+
+    /// Formats the nudge step.
+    pub fn fmt_nudge(&self) -> String {
+        format!("Nudge step: {:.03}", self.nudge_step)
+    }
+
+    /// Formats the speed factor `state` gets from proximity scaling, or `off`.
+    pub fn fmt_proximity_speed(&self, state: &FreeCameraState) -> String {
+        if self.proximity_speed_scaling {
+            format!("Proximity speed: x{:.02}", state.proximity_speed_factor)
+        } else {
+            "Proximity speed: off".to_string()
+        }
+    }
+}
+
+/// Tracks the runtime state of a [`FreeCamera`] controller.
+///
+/// This component holds dynamic data that changes during camera operation,
+/// such as pitch, yaw, velocity, and whether the controller is currently enabled.
+///
+/// It is automatically added to any entity that has a [`FreeCamera`] component,
+/// and is updated by the [`FreeCameraPlugin`] systems in response to user input.
+#[derive(Component)]
+pub struct FreeCameraState {
+    /// Enables [`FreeCamera`] controls when `true`.
+    pub enabled: bool,
+    /// Internal flag indicating if this controller has been initialized by the [`FreeCameraPlugin`].
+    initialized: bool,
+    /// Internal flag set when the next mouse delta should be thrown away instead of applied.
+    ///
+    /// Armed on startup, while the controller is disabled, when the window regains focus and
+    /// when the cursor gets grabbed, since those are the moments the OS tends to report one
+    /// large, meaningless jump.
+    discard_next_look: bool,
+    /// Snap turn or leveling in progress, started by [`snap_yaw`](FreeCameraState::snap_yaw)
+    /// or [`level_out`](FreeCameraState::level_out).
+    look_tween: Option<LookTween>,
+    /// This [`FreeCamera`]'s pitch rotation.
+    pub pitch: f32,
+    /// This [`FreeCamera`]'s yaw rotation.
+    pub yaw: f32,
+    /// Multiplier applied to [`FreeCamera::walk_speed`] and [`FreeCamera::run_speed`].
+    pub speed_multiplier: f32,
+    /// Multiplier set by scrolling and [`adjust_speed`](FreeCameraState::adjust_speed), which
+    /// [`speed_multiplier`](FreeCameraState::speed_multiplier) eases towards over
+    /// [`FreeCamera::scroll_smoothing`].
+    pub target_speed_multiplier: f32,
+    /// This [`FreeCamera`]'s translation velocity.
+    pub velocity: Vec3,
+    /// World space direction the movement keys pointed in during the last update.
+    input_direction: Vec3,
+    /// Whether the camera is currently moving with the run key held.
+    pub running: bool,
+    /// Field of view offset currently applied by the sprint kick, in radians.
+    pub sprint_fov_offset: f32,
+    /// How far into the sprint kick the field of view is, from `0.0` to `1.0`.
+    pub sprint_fov_progress: f32,
+    /// Speed factor currently applied by [`FreeCamera::proximity_speed_scaling`], `1.0` while
+    /// it is off.
+    pub proximity_speed_factor: f32,
+    /// How much of the movement and look input currently reaches the camera, from `0.0` to
+    /// `1.0`. Follows [`enabled`](FreeCameraState::enabled) over
+    /// [`FreeCamera::enable_transition`].
+    pub input_weight: f32,
+    /// How far into a crouch the camera is, from `0.0` (standing) to `1.0` (fully crouched).
+    pub crouch: f32,
+    /// Height of the camera above the feet of its body in [`MovementMode::Walk`], between
+    /// [`FreeCamera::stand_height`] and [`FreeCamera::crouch_height`] depending on
+    /// [`crouch`](FreeCameraState::crouch).
+    ///
+    /// Collisions and the floor clamp work on the feet, the camera renders this far above
+    /// them. `0.0` while flying, where the body is centered on the camera.
+    pub eye_height: f32,
+    /// Whether the camera is standing on a [`CameraCollider`] in [`MovementMode::Walk`].
+    pub grounded: bool,
+    /// Where the camera last stood on the ground in [`MovementMode::Walk`], which
+    /// [`Action::ReturnToGround`] and [`FreeCamera::kill_plane`] move it back to.
+    pub last_grounded_position: Option<Vec3>,
+    /// Whether a collider stopped the camera along a horizontal axis during the last update,
+    /// or along any axis while flying.
+    pub sliding: bool,
+    /// World space velocity along the wall being slid on, on top of the movement, left over
+    /// from the speed the camera hit it with. Eases to zero over
+    /// [`FreeCamera::slide_smoothing`].
+    pub slide_velocity: Vec3,
+    /// Distance to the point the camera turns around in the orbiting [`ControlScheme`]s.
+    pub focus_distance: f32,
+    /// The setting the scroll wheel changed most recently and its new value.
+    ///
+    /// Field of view is in radians and excludes the sprint kick.
+    pub last_scroll: Option<(ScrollTarget, f32)>,
+    /// The [`CameraCollider`]s that stopped the camera during the last update.
+    pub contacts: Vec<Entity>,
+    /// Movement actions pressed within the last [`FreeCamera::input_buffer_ms`], with the
+    /// real time they were pressed at.
+    pub buffered_presses: Vec<(Action, Duration)>,
+}
+
+impl Default for FreeCameraState {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            initialized: false,
+            discard_next_look: true,
+            look_tween: None,
+            pitch: 0.0,
+            yaw: 0.0,
+            speed_multiplier: 1.0,
+            target_speed_multiplier: 1.0,
+            velocity: Vec3::ZERO,
+            input_direction: Vec3::ZERO,
+            running: false,
+            sprint_fov_offset: 0.0,
+            sprint_fov_progress: 0.0,
+            proximity_speed_factor: 1.0,
+            input_weight: 1.0,
+            crouch: 0.0,
+            eye_height: 0.0,
+            grounded: false,
+            last_grounded_position: None,
+            sliding: false,
+            slide_velocity: Vec3::ZERO,
+            focus_distance: 10.0,
+            last_scroll: None,
+            contacts: Vec::new(),
+            buffered_presses: Vec::new(),
+        }
+    }
+}
+
+impl FreeCameraState {
+    /// Returns the world space direction the movement keys pointed in during the last update,
+    /// normalized, or zero if none were held.
+    ///
+    /// This is what the user asked for, before speed, friction and collisions shape it into
+    /// [`velocity`](FreeCameraState::velocity).
+    pub fn input_direction(&self) -> Vec3 {
+        self.input_direction
+    }
+
+    /// Returns where the feet of the body are for a camera at `eye`, [`eye_height`] below it
+    /// along the [`up`](FreeCamera::up) direction of `config`.
+    ///
+    /// [`eye_height`]: FreeCameraState::eye_height
+    pub fn feet_position(&self, config: &FreeCamera, eye: Vec3) -> Vec3 {
+        eye - config.up_rotation() * Vec3::Y * self.eye_height
+    }
+
+    /// Formats the current speed.
+    pub fn fmt_velocity(&self) -> String {
+        format!("Speed: {:.02}", self.velocity.length())
+    }
+
+    /// Changes the speed multiplier by `steps` times [`FreeCamera::scroll_factor`], like
+    /// scrolling the same number of notches would. Returns the new target multiplier.
+    pub fn adjust_speed(&mut self, config: &FreeCamera, steps: f32) -> f32 {
+        // Step from the target rather than the eased value, so quick steps add up
+        self.target_speed_multiplier =
+            (self.target_speed_multiplier + steps * config.scroll_factor).max(0.0);
+        self.target_speed_multiplier
+    }
+
+    /// Formats what the scroll wheel changed most recently and its new value.
+    pub fn fmt_last_scroll(&self) -> String {
+        let last_scroll = match self.last_scroll {
+            Some((ScrollTarget::Speed, multiplier)) => format!("speed x{multiplier:.02}"),
+            Some((ScrollTarget::Zoom, distance)) => format!("focus distance {distance:.02}"),
+            Some((ScrollTarget::Fov, fov)) => format!("FOV {:.01}°", fov.to_degrees()),
+            Some((ScrollTarget::NearPlane, near)) => format!("near plane {near:.03}"),
+            None => "-".to_string(),
+        };
+        format!("Last scroll: {last_scroll}")
+    }
+
+    /// Returns the rotation the tracked [`yaw`](FreeCameraState::yaw) and
+    /// [`pitch`](FreeCameraState::pitch) describe for `config`.
+    pub fn look_rotation(&self, config: &FreeCamera) -> Quat {
+        config.look_rotation(self.yaw, self.pitch)
+    }
+
+    /// Returns the direction to the right of the [`look_rotation`](Self::look_rotation).
+    pub fn right(&self, config: &FreeCamera) -> Dir3 {
+        self.look_rotation(config) * Dir3::X
+    }
+
+    /// Returns the direction up from the [`look_rotation`](Self::look_rotation), which leans
+    /// away from [`FreeCamera::up`] as the camera pitches.
+    pub fn up(&self, config: &FreeCamera) -> Dir3 {
+        self.look_rotation(config) * Dir3::Y
+    }
+
+    /// Returns the direction the [`look_rotation`](Self::look_rotation) looks in.
+    pub fn forward(&self, config: &FreeCamera) -> Dir3 {
+        self.look_rotation(config) * Dir3::NEG_Z
+    }
+
+    /// Sets the tracked yaw and pitch from `rotation`, so mouse look carries on from it
+    /// instead of snapping back. Cancels any snap turn or leveling in progress.
+    ///
+    /// Only the state is updated, set the camera's [`Transform`] rotation as well. The
+    /// controller has no roll, any roll in `rotation` is dropped. Pitch is clamped to straight
+    /// up or down, which also keeps rotations looking exactly along
+    /// [`FreeCamera::up`] from flipping the yaw.
+    pub fn set_look_rotation(&mut self, config: &FreeCamera, rotation: Quat) {
+        let (yaw, pitch, _roll) =
+            (config.up_rotation().inverse() * rotation).to_euler(EulerRot::YXZ);
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-FRAC_PI_2, FRAC_PI_2);
+        self.look_tween = None;
+    }
+
+    /// Turns the camera right by `turn.x` and down by `turn.y` radians, keeping the pitch
+    /// between straight down and straight up.
+    pub fn turn(&mut self, turn: Vec2) {
+        self.pitch = (self.pitch - turn.y).clamp(-FRAC_PI_2, FRAC_PI_2);
+        self.yaw -= turn.x;
+    }
+
+    /// Forgets the buffered presses older than [`FreeCamera::input_buffer_ms`] at `now`, in
+    /// real time.
+    pub fn prune_buffered_presses(&mut self, config: &FreeCamera, now: Duration) {
+        let buffer = Duration::from_secs_f32(config.input_buffer_ms.max(0.0) / 1000.0);
+        self.buffered_presses
+            .retain(|(_, pressed_at)| now.saturating_sub(*pressed_at) <= buffer);
+    }
+
+    /// Returns `true` if `action` was pressed within the input buffer.
+    pub fn is_buffered(&self, action: Action) -> bool {
+        self.buffered_presses
+            .iter()
+            .any(|(buffered, _)| *buffered == action)
+    }
+
+    /// Eases [`crouch`](FreeCameraState::crouch) towards `target` and moves `translation` down
+    /// or up with the eye height, staying crouched while standing up would push the body into
+    /// one of `colliders`.
+    ///
+    /// Only walking cameras crouch, flying ones stand straight away without moving.
+    pub fn update_crouch(
+        &mut self,
+        config: &FreeCamera,
+        translation: &mut Vec3,
+        walking: bool,
+        target: f32,
+        colliders: &[Aabb3d],
+        dt: f32,
+    ) {
+        if !walking {
+            self.crouch = 0.0;
+            self.eye_height = 0.0;
+            return;
+        }
+
+        let mut crouch = self.crouch;
+        crouch.smooth_nudge(&target, config.crouch_transition_speed, dt);
+        if (crouch - target).abs() < 1e-4 {
+            crouch = target;
+        }
+        let crouch_drop = config.eye_height(self.crouch) - config.eye_height(crouch);
+        let crouched_translation = *translation - config.up_rotation() * Vec3::Y * crouch_drop;
+        // Don't stand back up into a collider, stay crouched until there is room
+        let standing_blocked = crouch < self.crouch && {
+            let before = CollisionBody::new(config, self.crouch).bounds(*translation);
+            let after = CollisionBody::new(config, crouch).bounds(crouched_translation);
+            colliders
+                .iter()
+                .any(|collider| overlaps(&after, collider) && !overlaps(&before, collider))
+        };
+        if !standing_blocked {
+            *translation = crouched_translation;
+            self.crouch = crouch;
+        }
+        self.eye_height = config.eye_height(self.crouch);
+    }
+
+    /// Moves `transform` back to [`last_grounded_position`](FreeCameraState::last_grounded_position)
+    /// and stops the camera there. Returns `false` if it never stood on the ground.
+    pub fn return_to_ground(&mut self, transform: &mut Transform) -> bool {
+        let Some(position) = self.last_grounded_position else {
+            return false;
+        };
+        transform.translation = position;
+        self.velocity = Vec3::ZERO;
+        self.slide_velocity = Vec3::ZERO;
+        true
+    }
+
+    /// Returns a walking camera to the ground when `return_requested` or when it fell below
+    /// [`FreeCamera::kill_plane`], and otherwise remembers where it stands on the ground.
+    pub fn track_ground(
+        &mut self,
+        config: &FreeCamera,
+        transform: &mut Transform,
+        return_requested: bool,
+    ) {
+        let below_kill_plane = config
+            .kill_plane
+            .is_some_and(|kill_plane| transform.translation.y < kill_plane);
+        if return_requested || below_kill_plane {
+            self.return_to_ground(transform);
+        } else if self.grounded {
+            self.last_grounded_position = Some(transform.translation);
+        }
+    }
+
+    /// Moves `transform` to the nearest point on a grid with the given spacing and stops the
+    /// camera there. Returns the new translation.
+    ///
+    /// A `step` that isn't positive and finite leaves the translation as it is.
+    pub fn snap_to_grid(&mut self, transform: &mut Transform, step: f32) -> Vec3 {
+        if step > 0.0 && step.is_finite() {
+            transform.translation = (transform.translation / step).round() * step;
+        }
+        self.velocity = Vec3::ZERO;
+        transform.translation
+    }
+
+    /// Turns the camera to the nearest multiple of `increment` radians of yaw.
+    ///
+    /// The turn is eased over [`FreeCamera::snap_duration`] and overrides mouse look until it
+    /// completes. Use [`snap_yaw_by`](FreeCameraState::snap_yaw_by) to also step to a
+    /// neighbouring multiple.
+    pub fn snap_yaw(&mut self, increment: f32) {
+        self.snap_yaw_by(increment, 0);
+    }
+
+    /// Turns the camera `steps` multiples of `increment` radians away from the nearest
+    /// multiple of its current yaw. Positive steps turn left.
+    ///
+    /// Snapping again while a turn is in progress continues from where that turn was heading,
+    /// so quick repeated presses add up instead of being lost. The final yaw is wrapped into
+    /// `[0, 2π)`, and the camera always takes the shorter way around.
+    pub fn snap_yaw_by(&mut self, increment: f32, steps: i32) {
+        if increment <= 0.0 || !increment.is_finite() {
+            return;
+        }
+        let base = self.look_tween.map_or(self.yaw, |tween| tween.yaw);
+        let target = (((base / increment).round() + steps as f32) * increment).rem_euclid(TAU);
+        let turn = (target - self.yaw + PI).rem_euclid(TAU) - PI;
+        self.look_tween = Some(LookTween {
+            from: None,
+            yaw: self.yaw + turn,
+            pitch: self.look_tween.map_or(self.pitch, |tween| tween.pitch),
+            kind: LookTweenKind::Snap,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Levels the camera out, easing pitch and roll back to zero over
+    /// [`FreeCamera::level_duration`] while keeping the yaw.
+    ///
+    /// Mouse look is suspended until it completes. Leveling during a snap turn keeps the yaw
+    /// the turn was heading for.
+    pub fn level_out(&mut self) {
+        self.look_tween = Some(LookTween {
+            from: None,
+            yaw: self.look_tween.map_or(self.yaw, |tween| tween.yaw),
+            pitch: 0.0,
+            kind: LookTweenKind::Level,
+            elapsed: 0.0,
+        });
+    }
+}
+
+/// An eased turn from the camera's rotation when it started to a yaw and pitch.
+#[derive(Clone, Copy)]
+struct LookTween {
+    /// Rotation the tween started at, taken from the camera on its first update.
+    from: Option<Quat>,
+    yaw: f32,
+    pitch: f32,
+    kind: LookTweenKind,
+    elapsed: f32,
+}
+
+/// What started a [`LookTween`], which decides how long it takes.
+#[derive(Clone, Copy)]
+enum LookTweenKind {
+    Snap,
+    Level,
+}
+
+/// Cursor grab bookkeeping of [`run_freecamera_controller`].
+#[derive(Default)]
+pub struct CursorGrab {
+    /// Grabbed with [`Action::ToggleCursorGrab`].
+    toggled: bool,
+    /// Grabbed while the grab button is held.
+    held: bool,
+    /// The [`CursorMode`] applied to the grabbed cursor, `None` while it isn't grabbed.
+    mode: Option<CursorMode>,
+}
+
+impl CursorGrab {
+    /// Returns `true` while the cursor is grabbed by either the toggle or the held button.
+    pub fn is_grabbed(&self) -> bool {
+        self.held || self.toggled
+    }
+
+    /// Returns the mode the cursor should be in, or `None` if it should be released because
+    /// it isn't grabbed or grabbing isn't `enabled`.
+    pub fn target_mode(&self, enabled: bool, mode: CursorMode) -> Option<CursorMode> {
+        (enabled && self.is_grabbed()).then_some(mode)
+    }
+
+    /// Returns `true` if the cursor hasn't been put in the `target` mode yet.
+    pub fn mode_changed(&self, target: Option<CursorMode>) -> bool {
+        self.mode != target
+    }
+}
+
+/// Updates the camera's position and orientation based on user input.
+///
+/// - [`FreeCamera`] contains static configuration such as movement speed and sensitivity.
+/// - [`InputMap<Action>`] holds the key and button bindings.
+/// - [`FreeCameraState`] stores the dynamic runtime state, including pitch, yaw, velocity, and enable flags.
+///
+/// This system is typically added via the [`FreeCameraPlugin`].
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn run_freecamera_controller(
+    time: Res<Time<Real>>,
+    fixed_time: Res<Time<Fixed>>,
+    settings: Res<FreeCameraSettings>,
+    mut last_input_time: Local<Option<Duration>>,
+    mut capture_cursor: Local<bool>,
+    mut awaiting_click: Local<bool>,
+    mut windows: Query<(&Window, &mut CursorOptions)>,
+    mut focus_events: MessageReader<WindowFocused>,
+    accumulated_mouse_motion: Res<AccumulatedMouseMotion>,
+    accumulated_mouse_scroll: Res<AccumulatedMouseScroll>,
+    actions: ActionInput<Action>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    mut grab: Local<CursorGrab>,
+    mut query: Query<(
+        &Camera,
+        &mut Transform,
+        &mut FreeCameraState,
+        &FreeCamera,
+        Option<&mut Projection>,
+        Option<&mut SpringArm>,
+        Has<LookAtTarget>,
+    )>,
+    collider_query: Query<(Entity, &CameraCollider, &Aabb, &GlobalTransform)>,
+) {
+    let dt = if settings.run_in_fixed_update {
+        fixed_time.delta_secs()
+    } else {
+        time.delta_secs()
+    };
+    // Input is collected per frame, so only the first fixed tick of a frame gets to use it.
+    // Real time advances exactly once per frame.
+    let fresh_input = *last_input_time != Some(time.elapsed());
+    *last_input_time = Some(time.elapsed());
+
+    let Ok((
+        camera,
+        mut transform,
+        mut state,
+        config,
+        mut projection,
+        mut spring_arm,
+        has_look_target,
+    )) = query.single_mut()
+    else {
+        return;
+    };
+
+    // Only the camera that is rendering is controlled, another one may be showing the scene
+    if !camera.is_active {
+        state.velocity = Vec3::ZERO;
+        state.discard_next_look = true;
+        return;
+    }
+
+    let (collider_entities, colliders): (Vec<Entity>, Vec<Aabb3d>) = collider_query
+        .iter()
+        .filter(|(_, collider, ..)| collider.blocks(config))
+        .map(|(entity, _, aabb, global_transform)| {
+            (entity, collider_bounds(aabb, global_transform))
+        })
+        .unzip();
+
+    if !state.initialized {
+        state.set_look_rotation(config, transform.rotation);
+        state.focus_distance = config.orbit_distance;
+        state.initialized = true;
+        info!("{}", actions.input_map());
+
+        if settings.capture_cursor_on_start {
+            if cfg!(target_arch = "wasm32") {
+                *awaiting_click = true;
+            } else {
+                grab.toggled = true;
+                *capture_cursor = true;
+            }
+        }
+    }
+
+    if focus_events.read().any(|event| event.focused) {
+        state.discard_next_look = true;
+    }
+
+    // Fade input in and out instead of cutting it when the controller is toggled
+    let target_weight = if state.enabled { 1.0 } else { 0.0 };
+    state.input_weight = if config.enable_transition > 0.0 {
+        let step = dt / config.enable_transition;
+        state.input_weight + (target_weight - state.input_weight).clamp(-step, step)
+    } else {
+        target_weight
+    };
+
+    if !state.enabled {
+        // Whatever motion piled up while disabled should not be applied on re-enable.
+        state.discard_next_look = true;
+        state.running = false;
+        if state.input_weight == 0.0 {
+            state.velocity = Vec3::ZERO;
+            return;
+        }
+    }
+
+    // While fading out the camera only coasts, all input is ignored
+    let input_enabled = state.enabled;
+    let pressed = |action| input_enabled && actions.pressed(action);
+    let just_pressed = |action| input_enabled && fresh_input && actions.just_pressed(action);
+    let mouse_delta = if fresh_input {
+        let scale_factor = windows
+            .iter()
+            .find(|(window, _)| window.focused)
+            .map_or(1.0, |(window, _)| window.scale_factor());
+        PlatformMotionSource::look_delta(accumulated_mouse_motion.delta, scale_factor)
+            * config.device_sensitivity_scale()
+    } else {
+        Vec2::ZERO
+    };
+
+    let mut scroll = 0.0;
+
+    let amount = match accumulated_mouse_scroll.unit {
+        MouseScrollUnit::Line => accumulated_mouse_scroll.delta.y,
+        MouseScrollUnit::Pixel => {
+            accumulated_mouse_scroll.delta.y / MouseScrollUnit::SCROLL_UNIT_CONVERSION_FACTOR
+        }
+    };
+    if input_enabled && fresh_input {
+        scroll += if config.invert_scroll {
+            -amount
+        } else {
+            amount
+        };
+    }
+    let orbiting = config.control_scheme != ControlScheme::Fps;
+    if let Some(arm) = spring_arm.as_mut()
+        && orbiting
+    {
+        // Turn and zoom from the full arm length, it's pulled in again at the end
+        let pulled_in = (state.focus_distance - arm.length).max(0.0);
+        let back = -transform.forward();
+        transform.translation += back * pulled_in;
+    }
+    let scroll_target = if pressed(Action::ScrollFov) {
+        ScrollTarget::Fov
+    } else if pressed(Action::ScrollNear) {
+        ScrollTarget::NearPlane
+    } else if orbiting {
+        ScrollTarget::Zoom
+    } else {
+        ScrollTarget::Speed
+    };
+    // Modifiers can share keys with running and crouching, which shouldn't change while scrolling
+    let modified_scroll = scroll != 0.0 && scroll_target.is_modified();
+    if scroll != 0.0 {
+        let value = match (scroll_target, projection.as_deref_mut()) {
+            (ScrollTarget::Speed, _) => Some(state.adjust_speed(config, scroll)),
+            (ScrollTarget::Zoom, _) => {
+                // Move along the view direction, keeping the focus point where it is
+                let focus = transform.translation + transform.forward() * state.focus_distance;
+                state.focus_distance = (state.focus_distance * 0.9f32.powf(scroll)).max(0.1);
+                transform.translation = focus - transform.forward() * state.focus_distance;
+                Some(state.focus_distance)
+            }
+            (ScrollTarget::Fov, Some(Projection::Perspective(perspective))) => {
+                // Keep the sprint kick out of the clamp so it still unwinds to the new base value
+                let base_fov = (perspective.fov - state.sprint_fov_offset
+                    + scroll * config.scroll_fov_step)
+                    .clamp(1f32.to_radians(), 170f32.to_radians());
+                perspective.fov = base_fov + state.sprint_fov_offset;
+                Some(base_fov)
+            }
+            (ScrollTarget::NearPlane, Some(Projection::Perspective(perspective))) => {
+                perspective.near = (perspective.near
+                    * (1.0 + config.scroll_near_factor).powf(scroll))
+                .clamp(1e-4, perspective.far * 0.5);
+                Some(perspective.near)
+            }
+            _ => None,
+        };
+        if let Some(value) = value {
+            state.last_scroll = Some((scroll_target, value));
+        }
+    }
+
+    // Handle key input, counting keys tapped within the input buffer as held
+    state.prune_buffered_presses(config, time.elapsed());
+    let mut axis_input =
+        movement_axes(|action| pressed(action) || input_enabled && state.is_buffered(action));
+    if orbiting {
+        axis_input = Vec3::ZERO;
+    }
+
+    let mut cursor_grab_change = std::mem::take(&mut *capture_cursor);
+    // Browsers need a click before they lock the pointer, and let go of it on Escape without
+    // telling us. Capture on the first click, and lock again on any click while grabbed.
+    if cfg!(target_arch = "wasm32")
+        && input_enabled
+        && config.cursor_grab_enabled
+        && mouse_button_input.get_just_pressed().next().is_some()
+    {
+        if std::mem::take(&mut *awaiting_click) {
+            grab.toggled = true;
+        }
+        cursor_grab_change |= grab.toggled;
+    }
+    if config.cursor_grab_enabled && just_pressed(Action::ToggleCursorGrab) {
+        grab.toggled = !grab.toggled;
+        cursor_grab_change = true;
+    }
+    // The CAD scheme only looks around while its orbit button is held
+    let grab_action = match config.control_scheme {
+        ControlScheme::Cad => Action::Orbit,
+        _ => Action::GrabCursor,
+    };
+    if config.cursor_grab_enabled && just_pressed(grab_action) {
+        grab.held = true;
+        cursor_grab_change = true;
+    }
+    if input_enabled && fresh_input && actions.just_released(grab_action) {
+        grab.held = false;
+        cursor_grab_change = true;
+    }
+    let target_mode = grab.target_mode(config.cursor_grab_enabled, config.cursor_mode);
+    let cursor_grab = target_mode.is_some();
+    // Apply a new cursor mode to a grabbed cursor, and release or grab it again when grabbing
+    // is turned off or on
+    cursor_grab_change |= grab.mode_changed(target_mode);
+
+    // In walk mode the up key jumps instead of being part of the movement direction. Orbiting
+    // cameras always fly, so they don't drop away from their focus.
+    let walking = !orbiting && config.movement_mode == MovementMode::Walk;
+
+    // World space axes the movement keys map to
+    let (right, up, forward) = config.movement_frame(transform.rotation, state.yaw, walking);
+
+    // Record what was pressed before jumping, speed, friction and collisions get involved
+    state.input_direction =
+        (axis_input.x * right + axis_input.y * up + axis_input.z * forward).normalize_or_zero();
+
+    // Ease the speed towards the scrolled value
+    let target_speed = state.target_speed_multiplier;
+    if config.scroll_smoothing > 0.0 {
+        state
+            .speed_multiplier
+            .smooth_nudge(&target_speed, config.scroll_smoothing.recip(), dt);
+        if (state.speed_multiplier - target_speed).abs() < 1e-4 {
+            state.speed_multiplier = target_speed;
+        }
+    } else {
+        state.speed_multiplier = target_speed;
+    }
+
+    // Slow down near surfaces and speed up in the open
+    state.proximity_speed_factor = if config.proximity_speed_scaling {
+        let eye = transform.translation;
+        let nearest = collider_query
+            .iter()
+            .filter(|(_, collider, ..)| collider.layers.intersects(config.collision_mask))
+            .map(|(_, _, aabb, global_transform)| {
+                let closest = collider_bounds(aabb, global_transform).closest_point(eye);
+                eye.distance(closest.into())
+            })
+            .fold(f32::INFINITY, f32::min);
+        let max = config.proximity_speed_max;
+        (nearest / config.proximity_reference_distance.max(1e-3))
+            .clamp(config.proximity_speed_min.min(max), max)
+    } else {
+        1.0
+    };
+
+    // Update velocity and apply movement update
+    let step = integrate_camera(
+        &state,
+        &MovementInput {
+            axis: axis_input,
+            run: if modified_scroll {
+                state.running
+            } else {
+                pressed(Action::Run)
+            },
+            walking,
+            right,
+            up,
+            forward,
+            translation: transform.translation,
+            colliders: &colliders,
+        },
+        config,
+        dt,
+    );
+    transform.translation = step.translation;
+    state.velocity = step.velocity;
+    state.slide_velocity = step.slide_velocity;
+    state.running = step.running;
+    state.grounded = step.grounded;
+    state.sliding = step.sliding;
+    state.contacts = step
+        .contacts
+        .into_iter()
+        .map(|index| collider_entities[index])
+        .collect();
+
+    // Ease towards the crouched or standing eye height
+    let crouch_target = if !input_enabled || modified_scroll {
+        state.crouch
+    } else if pressed(Action::Crouch) {
+        1.0
+    } else {
+        0.0
+    };
+    state.update_crouch(
+        config,
+        &mut transform.translation,
+        walking,
+        crouch_target,
+        &colliders,
+        dt,
+    );
+
+    // Handle cursor grab
+    if cursor_grab_change {
+        if cursor_grab {
+            for (window, mut cursor_options) in &mut windows {
+                if !window.focused {
+                    continue;
+                }
+
+                (cursor_options.grab_mode, cursor_options.visible) =
+                    config.cursor_mode.grabbed_cursor();
+            }
+            grab.mode = Some(config.cursor_mode);
+            // Locking the cursor can warp it, which shows up as one large motion event.
+            state.discard_next_look = true;
+        } else {
+            for (_, mut cursor_options) in &mut windows {
+                cursor_options.grab_mode = CursorGrabMode::None;
+                cursor_options.visible = true;
+            }
+            grab.mode = None;
+        }
+    }
+
+    // Handle panning, which follows the mouse only while the button is held
+    if config.control_scheme == ControlScheme::Cad
+        && pressed(Action::Pan)
+        && mouse_delta != Vec2::ZERO
+    {
+        let delta = config.clamp_look_delta(mouse_delta);
+        let scale = config.pan_speed * state.focus_distance;
+        // Drag the scene along with the cursor
+        let offset = (transform.left() * delta.x + transform.up() * delta.y) * scale;
+        transform.translation += offset;
+    }
+
+    // Handle snap turns and leveling, which take over from the mouse until they complete
+    if just_pressed(Action::SnapLeft) {
+        state.snap_yaw_by(config.snap_increment, 1);
+    }
+    if just_pressed(Action::SnapRight) {
+        state.snap_yaw_by(config.snap_increment, -1);
+    }
+    if has_look_target {
+        // `run_freecamera_look_at` aims the camera instead
+    } else if let Some(mut tween) = state.look_tween {
+        let from = *tween.from.get_or_insert(transform.rotation);
+        tween.elapsed += dt;
+        let duration = match tween.kind {
+            LookTweenKind::Snap => config.snap_duration,
+            LookTweenKind::Level => config.level_duration,
+        };
+        let t = if duration > 0.0 {
+            (tween.elapsed / duration).min(1.0)
+        } else {
+            1.0
+        };
+        let rotation = if t < 1.0 {
+            let rotation = from.slerp(
+                config.look_rotation(tween.yaw, tween.pitch),
+                apply_ease(config.look_ease, t),
+            );
+            let (yaw, pitch, _roll) =
+                (config.up_rotation().inverse() * rotation).to_euler(EulerRot::YXZ);
+            state.yaw = yaw;
+            state.pitch = pitch;
+            state.look_tween = Some(tween);
+            rotation
+        } else {
+            // Land exactly on the target, so the yaw is kept bit for bit
+            state.yaw = tween.yaw.rem_euclid(TAU);
+            state.pitch = tween.pitch;
+            state.look_tween = None;
+            state.look_rotation(config)
+        };
+        rotate_camera(
+            &mut transform,
+            rotation,
+            orbiting.then_some(state.focus_distance),
+        );
+    } else if input_enabled {
+        let mut turn = Vec2::ZERO;
+
+        // Handle mouse input
+        if mouse_delta != Vec2::ZERO && cursor_grab {
+            if state.discard_next_look {
+                state.discard_next_look = false;
+            } else {
+                let delta = config.clamp_look_delta(mouse_delta) * state.input_weight;
+                turn = config.mouse_turn(delta);
+            }
+        }
+
+        // Handle keyboard and stick look, on top of the mouse
+        turn += look_axes(pressed) * config.keyboard_look_speed * dt * state.input_weight;
+        let stick = config.shape_gamepad_look(actions.right_stick());
+        turn += Vec2::new(stick.x, -stick.y) * config.gamepad_look_speed * dt * state.input_weight;
+
+        // Apply look update
+        if turn != Vec2::ZERO {
+            state.turn(turn);
+            let rotation = state.look_rotation(config);
+            rotate_camera(
+                &mut transform,
+                rotation,
+                orbiting.then_some(state.focus_distance),
+            );
+        }
+    }
+
+    // Keep the feet above the floor clamp and the camera below the ceiling clamp
+    let height = config.clamp_height(transform.translation.y, state.eye_height);
+    if height != transform.translation.y {
+        // Walking on the floor clamp counts as standing on the ground
+        if walking && height > transform.translation.y {
+            state.grounded = true;
+        }
+        transform.translation.y = height;
+        state.velocity.y = 0.0;
+    }
+
+    // Recover from falling through the floor, or remember the ground for when that happens
+    if walking {
+        state.track_ground(config, &mut transform, just_pressed(Action::ReturnToGround));
+    }
+
+    if let Some(arm) = spring_arm.as_mut() {
+        arm.length = if orbiting {
+            let focus = transform.translation + transform.forward() * state.focus_distance;
+            let back = -transform.forward();
+            let hit = cast_ray(
+                focus,
+                back,
+                collider_entities
+                    .iter()
+                    .copied()
+                    .zip(colliders.iter().copied()),
+            );
+            let length = arm.fit_length(state.focus_distance, hit, config.collision_radius);
+            transform.translation = focus + back * length;
+            length
+        } else {
+            f32::INFINITY
+        };
+    }
+}
+
+/// Input for one step of [`integrate_camera`], gathered from the actions and the scene.
+#[derive(Clone, Copy, Debug)]
+pub struct MovementInput<'a> {
+    /// Movement keys held, as right, up and forward components from `-1.0` to `1.0`.
+    ///
+    /// While walking a positive up component jumps instead.
+    pub axis: Vec3,
+    /// Whether [`Action::Run`] is held.
+    pub run: bool,
+    /// Whether the camera walks with gravity rather than flying.
+    pub walking: bool,
+    /// World space direction the right component moves in.
+    pub right: Vec3,
+    /// World space direction the up component and vertical speed move in.
+    pub up: Vec3,
+    /// World space direction the forward component moves in.
+    pub forward: Vec3,
+    /// Position of the camera before moving.
+    pub translation: Vec3,
+    /// World space bounds of the colliders the camera collides with.
+    pub colliders: &'a [Aabb3d],
+}
+
+/// Where one step of [`integrate_camera`] leaves the camera.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraStep {
+    /// Position of the camera after moving and colliding.
+    pub translation: Vec3,
+    /// See [`FreeCameraState::velocity`].
+    pub velocity: Vec3,
+    /// See [`FreeCameraState::slide_velocity`].
+    pub slide_velocity: Vec3,
+    /// See [`FreeCameraState::running`].
+    pub running: bool,
+    /// See [`FreeCameraState::grounded`].
+    pub grounded: bool,
+    /// See [`FreeCameraState::sliding`].
+    pub sliding: bool,
+    /// Indices into [`MovementInput::colliders`] of the colliders that blocked the movement.
+    pub contacts: Vec<usize>,
+}
+
+/// Advances the camera's velocity and position by `dt` seconds.
+///
+/// This is the movement part of [`run_freecamera_controller`]: acceleration towards the
+/// keys, friction, gravity and jumping, speed clamping, collisions and sliding along walls.
+/// It only reads its arguments, so it can be run outside of the ECS.
+pub fn integrate_camera(
+    state: &FreeCameraState,
+    input: &MovementInput,
+    config: &FreeCamera,
+    dt: f32,
+) -> CameraStep {
+    let walking = input.walking;
+    let (right, up, forward) = (input.right, input.up, input.forward);
+    let mut axis_input = input.axis;
+    let mut grounded = state.grounded;
+    let jump = walking && axis_input.y > 0.0;
+    if walking {
+        axis_input.y = 0.0;
+    } else {
+        grounded = false;
+    }
+
+    // Update velocity
+    let running = axis_input != Vec3::ZERO && input.run;
+    let mut velocity = state.velocity;
+    let vertical = velocity.y;
+    if axis_input != Vec3::ZERO {
+        let (walk_speed, run_speed) = config.effective_speeds(state);
+        let max_speed = if running { run_speed } else { walk_speed } * state.proximity_speed_factor;
+        let max_speed = if walking {
+            max_speed
+                * config.pitch_speed_factor(state.pitch)
+                * 1.0.lerp(
+                    config.crouch_speed_multiplier,
+                    apply_ease(config.transition_ease, state.crouch),
+                )
+        } else {
+            max_speed
+        };
+        let steered = axis_input.normalize() * max_speed;
+        velocity = if walking && !grounded && config.inertia {
+            // Only part of the way towards the keys, so jumps keep most of their momentum
+            let air_control = config.air_control.clamp(0.0, 1.0);
+            velocity.lerp(steered, 1.0 - (1.0 - air_control).powf(dt * 60.0))
+        } else {
+            steered
+        };
+    } else if !config.inertia {
+        velocity = Vec3::ZERO;
+    } else {
+        let friction = if walking && !grounded {
+            config.air_friction
+        } else {
+            config.friction
+        }
+        .clamp(0.0, f32::MAX);
+        velocity.smooth_nudge(&Vec3::ZERO, friction, dt);
+        if velocity.length_squared() < 1e-6 {
+            velocity = Vec3::ZERO;
+        }
+    }
+    if walking {
+        // Vertical speed is driven by gravity and jumping, never by friction
+        velocity.y = if jump && grounded {
+            config.jump_speed
+        } else {
+            (vertical - config.gravity * dt).max(-config.terminal_velocity.max(0.0))
+        };
+    }
+    if let Some(max_speed) = config.max_speed {
+        velocity = velocity.clamp_length_max(max_speed.max(0.0));
+    }
+
+    let mut step = CameraStep {
+        translation: input.translation,
+        velocity,
+        slide_velocity: state.slide_velocity,
+        running,
+        grounded,
+        sliding: false,
+        contacts: Vec::new(),
+    };
+    if velocity == Vec3::ZERO {
+        step.slide_velocity = Vec3::ZERO;
+        return step;
+    }
+
+    // Apply movement update
+    if config.slide_smoothing > 0.0 {
+        step.slide_velocity *= (-dt / config.slide_smoothing).exp();
+    } else {
+        step.slide_velocity = Vec3::ZERO;
+    }
+    let displacement = ((velocity.x * right + velocity.y * up + velocity.z * forward) * dt
+        + step.slide_velocity * dt)
+        * state.input_weight;
+
+    let body = CollisionBody::new(config, state.crouch);
+    let step_height = if walking && grounded {
+        config.step_height
+    } else {
+        0.0
+    };
+    let blocked = move_and_collide(
+        &mut step.translation,
+        displacement,
+        body,
+        input.colliders,
+        step_height,
+        &mut step.contacts,
+    );
+
+    // Walking on the floor isn't sliding
+    let mut slide_axes = blocked;
+    if walking {
+        slide_axes.y = false;
+    }
+    let sliding = slide_axes.any();
+    if sliding && !state.sliding && config.slide_smoothing > 0.0 && dt > 0.0 {
+        // Turn the speed the wall took away into extra speed along it
+        let plane = if walking {
+            Vec3::new(1.0, 0.0, 1.0)
+        } else {
+            Vec3::ONE
+        };
+        let intended = displacement / dt * plane;
+        let projected = Vec3::select(slide_axes, Vec3::ZERO, intended);
+        step.slide_velocity = projected.normalize_or_zero() * intended.length() - projected;
+    } else if sliding {
+        step.slide_velocity = Vec3::select(slide_axes, Vec3::ZERO, step.slide_velocity);
+    } else {
+        step.slide_velocity = Vec3::ZERO;
+    }
+    step.sliding = sliding;
+
+    if walking {
+        step.grounded = blocked.y && velocity.y <= 0.0;
+        if blocked.y {
+            step.velocity.y = 0.0;
+        }
+    }
+    step
+}
+
+/// Turns cameras with a [`LookAtTarget`] towards their target.
+pub fn run_freecamera_look_at(
+    time: Res<Time<Real>>,
+    fixed_time: Res<Time<Fixed>>,
+    settings: Res<FreeCameraSettings>,
+    mut query: Query<(
+        &mut Transform,
+        &mut FreeCameraState,
+        &FreeCamera,
+        &mut LookAtTarget,
+    )>,
+    target_query: Query<&GlobalTransform>,
+) {
+    let dt = if settings.run_in_fixed_update {
+        fixed_time.delta_secs()
+    } else {
+        time.delta_secs()
+    };
+
+    for (mut transform, mut state, config, mut look_at) in &mut query {
+        let Ok(target) = target_query.get(look_at.entity) else {
+            continue;
+        };
+        let Some(direction) = (target.translation() - transform.translation).try_normalize() else {
+            continue;
+        };
+        let up = config.up_rotation() * Vec3::Y;
+        let aim = Transform::default().looking_to(direction, up).rotation;
+
+        // Start a new turn whenever the target changes, from wherever the camera points now
+        let entity = look_at.entity;
+        let mut blend = match look_at.blend {
+            Some(blend) if blend.entity == entity => blend,
+            _ => LookAtBlend {
+                entity,
+                from: transform.rotation,
+                elapsed: 0.0,
+            },
+        };
+        blend.elapsed += dt;
+        let t = if look_at.blend_duration > 0.0 {
+            (blend.elapsed / look_at.blend_duration).min(1.0)
+        } else {
+            1.0
+        };
+        look_at.blend = Some(blend);
+        transform.rotation = blend.from.slerp(aim, apply_ease(config.look_ease, t));
+
+        // Keep yaw and pitch in step, so mouse look carries on from here afterwards
+        state.set_look_rotation(config, transform.rotation);
+    }
+}
+
+/// Returns the right, up and forward movement components for the movement actions `held`
+/// reports as held, each from `-1.0` to `1.0`.
+pub fn movement_axes(held: impl Fn(Action) -> bool) -> Vec3 {
+    let mut axes = Vec3::ZERO;
+    if held(Action::MoveForward) {
+        axes.z += 1.0;
+    }
+    if held(Action::MoveBack) {
+        axes.z -= 1.0;
+    }
+    if held(Action::MoveRight) {
+        axes.x += 1.0;
+    }
+    if held(Action::MoveLeft) {
+        axes.x -= 1.0;
+    }
+    if held(Action::MoveUp) {
+        axes.y += 1.0;
+    }
+    if held(Action::MoveDown) {
+        axes.y -= 1.0;
+    }
+    axes
+}
+
+/// Returns the turn right and down the look actions `held` reports as held ask for, each
+/// from `-1.0` to `1.0`.
+pub fn look_axes(held: impl Fn(Action) -> bool) -> Vec2 {
+    let mut axes = Vec2::ZERO;
+    if held(Action::LookRight) {
+        axes.x += 1.0;
+    }
+    if held(Action::LookLeft) {
+        axes.x -= 1.0;
+    }
+    if held(Action::LookDown) {
+        axes.y += 1.0;
+    }
+    if held(Action::LookUp) {
+        axes.y -= 1.0;
+    }
+    axes
+}
+
+/// Records presses of the movement actions for [`FreeCamera::input_buffer_ms`].
+///
+/// Runs every frame rather than with the controller, so taps in frames without a fixed tick
+/// are recorded too.
+pub fn buffer_movement_input(
+    time: Res<Time<Real>>,
+    actions: ActionInput<Action>,
+    mut query: Query<(&FreeCamera, &mut FreeCameraState)>,
+) {
+    for (config, mut state) in &mut query {
+        if config.input_buffer_ms <= 0.0 || !state.enabled {
+            continue;
+        }
+        for action in Action::MOVEMENT {
+            if actions.just_pressed(action) {
+                state.buffered_presses.push((action, time.elapsed()));
+            }
+        }
+    }
+}
+
+/// Interpolates the rendered transform of cameras whose controller runs in fixed update.
+fn add_previous_transform(add: On<Add, FreeCamera>, mut commands: Commands) {
+    commands
+        .entity(add.entity)
+        .insert_if_new(PreviousTransform::default());
+}
+
+/// Puts the simulated state of [`PreviousTransform`] entities back before a fixed tick runs.
+pub fn begin_fixed_transform(mut query: Query<(&mut Transform, &mut PreviousTransform)>) {
+    for (mut transform, mut previous) in &mut query {
+        previous.sync(&transform);
+        previous.transform = previous.current;
+        *transform = previous.current;
+    }
+}
+
+/// Records the simulated state of [`PreviousTransform`] entities after a fixed tick ran.
+pub fn end_fixed_transform(mut query: Query<(&Transform, &mut PreviousTransform)>) {
+    for (transform, mut previous) in &mut query {
+        previous.current = *transform;
+        previous.written = *transform;
+    }
+}
+
+/// Sets the rendered [`Transform`] of [`PreviousTransform`] entities between the last two
+/// fixed ticks.
+pub fn interpolate_fixed_transform(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &mut PreviousTransform)>,
+) {
+    let t = fixed_time.overstep_fraction();
+    for (mut transform, mut previous) in &mut query {
+        previous.sync(&transform);
+        *transform = interpolate_transform(&previous.transform, &previous.current, t);
+        previous.written = *transform;
+    }
+}
+
+/// Moves the camera by exactly [`FreeCamera::nudge_step`] along a world axis for each nudge
+/// [`Action`] pressed, bypassing velocity so the camera can be positioned precisely.
+pub fn run_freecamera_nudge(
+    actions: ActionInput<Action>,
+    mut query: Query<(&mut Transform, &FreeCameraState, &FreeCamera)>,
+) {
+    const NUDGES: [(Action, Vec3); 6] = [
+        (Action::NudgeLeft, Vec3::NEG_X),
+        (Action::NudgeRight, Vec3::X),
+        (Action::NudgeForward, Vec3::NEG_Z),
+        (Action::NudgeBack, Vec3::Z),
+        (Action::NudgeUp, Vec3::Y),
+        (Action::NudgeDown, Vec3::NEG_Y),
+    ];
+
+    for (mut transform, state, config) in &mut query {
+        if !state.enabled {
+            continue;
+        }
+
+        let direction: Vec3 = NUDGES
+            .iter()
+            .filter(|(action, _)| actions.just_pressed(*action))
+            .map(|(_, direction)| *direction)
+            .sum();
+        if direction == Vec3::ZERO {
+            continue;
+        }
+
+        let step = if actions.pressed(Action::Run) {
+            config.nudge_step * 10.0
+        } else {
+            config.nudge_step
+        };
+        transform.translation += direction * step;
+        transform.translation.y = config.clamp_height(transform.translation.y, state.eye_height);
+    }
+}
+
+/// Sets the camera's rotation, turning it around the point `focus_distance` in front of it
+/// instead of in place if given.
+fn rotate_camera(transform: &mut Transform, rotation: Quat, focus_distance: Option<f32>) {
+    if let Some(distance) = focus_distance {
+        let focus = transform.translation + transform.forward() * distance;
+        transform.translation = focus - rotation * Vec3::NEG_Z * distance;
+    }
+    transform.rotation = rotation;
+}
+
+/// Eases the camera's field of view towards [`FreeCamera::sprint_fov_delta`] while running
+/// and back to its base value otherwise.
+///
+/// Only the difference from the previously applied offset is written to the projection,
+/// which lets this cooperate with anything else that changes the field of view.
+pub fn run_freecamera_sprint_fov(
+    time: Res<Time<Real>>,
+    mut query: Query<(&mut Projection, &mut FreeCameraState, &FreeCamera)>,
+) {
+    for (mut projection, mut state, config) in &mut query {
+        let target = if state.running { 1.0 } else { 0.0 };
+
+        let mut progress = state.sprint_fov_progress;
+        progress.smooth_nudge(&target, config.sprint_fov_speed, time.delta_secs());
+        if (progress - target).abs() < 1e-4 {
+            progress = target;
+        }
+        state.sprint_fov_progress = progress;
+        let offset = config.sprint_fov_delta * apply_ease(config.transition_ease, progress);
+        if offset == state.sprint_fov_offset {
+            continue;
+        }
+
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov += offset - state.sprint_fov_offset;
+            state.sprint_fov_offset = offset;
+        }
+    }
+}
+
+/// Box around the camera that is kept out of [`CameraCollider`]s, relative to the eye position.
+#[derive(Clone, Copy)]
+struct CollisionBody {
+    radius: f32,
+    below: f32,
+    above: f32,
+}
+
+impl CollisionBody {
+    fn new(config: &FreeCamera, crouch: f32) -> Self {
+        let radius = config.collision_radius;
+        match config.movement_mode {
+            MovementMode::Fly => Self {
+                radius,
+                below: radius,
+                above: radius,
+            },
+            // Reach down to the feet so the body can stand on colliders
+            MovementMode::Walk => Self {
+                radius,
+                below: config.eye_height(crouch),
+                above: radius,
+            },
+        }
+    }
+
+    fn bounds(&self, eye: Vec3) -> Aabb3d {
+        Aabb3d::new(
+            eye + Vec3::Y * (self.above - self.below) / 2.0,
+            Vec3::new(self.radius, (self.above + self.below) / 2.0, self.radius),
+        )
+    }
+}
+
+/// World space bounds of a collider.
+pub fn collider_bounds(aabb: &Aabb, global_transform: &GlobalTransform) -> Aabb3d {
+    let affine = global_transform.affine();
+    let center = affine.transform_point3a(aabb.center);
+    let half_extents = affine.matrix3.abs() * aabb.half_extents;
+    Aabb3d {
+        min: center - half_extents,
+        max: center + half_extents,
+    }
+}
+
+/// Where a ray cast by [`SceneRaycast`] hit a [`CameraCollider`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    /// World space point the ray entered the collider at.
+    pub point: Vec3,
+    /// Outward normal of the face that was hit.
+    pub normal: Dir3,
+    /// Distance along the ray to [`point`](RayHit::point).
+    pub distance: f32,
+    /// The collider that was hit.
+    pub entity: Entity,
+}
+
+/// Casts rays against the world space bounds of [`CameraCollider`]s, e.g. to find what the
+/// camera looks at or where to place something flush against a wall.
+///
+/// Colliders on every layer are hit, and like collisions this works on the bounding boxes
+/// rather than the meshes themselves.
+#[derive(SystemParam)]
+pub struct SceneRaycast<'w, 's> {
+    colliders:
+        Query<'w, 's, (Entity, &'static Aabb, &'static GlobalTransform), With<CameraCollider>>,
+}
+
+impl SceneRaycast<'_, '_> {
+    /// Returns the nearest collider along the ray from `origin` in `direction`, if any.
+    ///
+    /// Colliders that contain `origin` are skipped, so a ray cast from a camera standing in
+    /// a trigger volume still finds the wall behind it.
+    pub fn raycast_scene(&self, origin: Vec3, direction: Dir3) -> Option<RayHit> {
+        cast_ray(
+            origin,
+            direction,
+            self.colliders
+                .iter()
+                .map(|(entity, aabb, global_transform)| {
+                    (entity, collider_bounds(aabb, global_transform))
+                }),
+        )
+    }
+}
+
+/// Returns the nearest of `colliders` along the ray from `origin` in `direction`, skipping
+/// the ones that contain `origin`.
+fn cast_ray(
+    origin: Vec3,
+    direction: Dir3,
+    colliders: impl IntoIterator<Item = (Entity, Aabb3d)>,
+) -> Option<RayHit> {
+    let ray = RayCast3d::new(origin, direction, f32::INFINITY);
+    colliders
+        .into_iter()
+        .filter_map(|(entity, bounds)| {
+            let distance = ray.aabb_intersection_at(&bounds)?;
+            (distance > 0.0).then_some((entity, bounds, distance))
+        })
+        .min_by(|(.., a), (.., b)| a.total_cmp(b))
+        .map(|(entity, bounds, distance)| {
+            let point = origin + direction * distance;
+            RayHit {
+                point,
+                normal: face_normal(&bounds, point),
+                distance,
+                entity,
+            }
+        })
+}
+
+/// Returns the outward normal of the face of `bounds` nearest to `point` on its surface.
+fn face_normal(bounds: &Aabb3d, point: Vec3) -> Dir3 {
+    let half_size = Vec3::from(bounds.half_size()).max(Vec3::splat(f32::EPSILON));
+    // Position relative to the center, in half sizes, so the face hit is at ±1 on its axis
+    let relative = (point - Vec3::from(bounds.center())) / half_size;
+    let axis = relative.abs().max_position();
+    let mut normal = Vec3::ZERO;
+    normal[axis] = relative[axis].signum();
+    Dir3::new_unchecked(normal)
+}
+
+fn overlaps(a: &Aabb3d, b: &Aabb3d) -> bool {
+    a.min.cmplt(b.max).all() && a.max.cmpgt(b.min).all()
+}
+
+/// Moves `eye` by `displacement`, one axis at a time, stopping at any collider entered on the way.
+///
+/// Horizontal movement that runs into a collider whose top is at most `step_height` above the
+/// feet steps up onto it instead. Colliders the body already overlaps are ignored, so the
+/// camera can always move out of them. Returns which axes were blocked, and adds the index of
+/// every collider that blocked them to `contacts`.
+fn move_and_collide(
+    eye: &mut Vec3,
+    displacement: Vec3,
+    body: CollisionBody,
+    colliders: &[Aabb3d],
+    step_height: f32,
+    contacts: &mut Vec<usize>,
+) -> BVec3 {
+    if colliders.is_empty() {
+        *eye += displacement;
+        return BVec3::FALSE;
+    }
+
+    // Split fast moves into steps smaller than the body so thin walls can't be skipped
+    let max_step = body.radius.max(0.05);
+    let steps = (displacement.abs().max_element() / max_step)
+        .ceil()
+        .clamp(1.0, 64.0);
+    let step = displacement / steps;
+
+    let mut blocked = BVec3::FALSE;
+    for _ in 0..steps as usize {
+        for axis in [0, 2, 1] {
+            if step[axis] != 0.0
+                && move_axis(
+                    eye,
+                    axis,
+                    step[axis],
+                    body,
+                    colliders,
+                    step_height,
+                    contacts,
+                )
+            {
+                blocked.set(axis, true);
+            }
+        }
+    }
+    blocked
+}
+
+fn move_axis(
+    eye: &mut Vec3,
+    axis: usize,
+    amount: f32,
+    body: CollisionBody,
+    colliders: &[Aabb3d],
+    step_height: f32,
+    contacts: &mut Vec<usize>,
+) -> bool {
+    let before = body.bounds(*eye);
+    let mut moved = *eye;
+    moved[axis] += amount;
+
+    let mut blocked = false;
+    for (index, collider) in colliders.iter().enumerate() {
+        let after = body.bounds(moved);
+        if !overlaps(&after, collider) || overlaps(&before, collider) {
+            continue;
+        }
+
+        if axis != 1 && step_height > 0.0 {
+            let rise = collider.max.y - after.min.y;
+            if rise > 0.0 && rise <= step_height {
+                let mut raised = moved;
+                raised.y += rise + COLLISION_SKIN;
+                let raised_bounds = body.bounds(raised);
+                if !colliders
+                    .iter()
+                    .any(|other| overlaps(&raised_bounds, other))
+                {
+                    moved = raised;
+                    continue;
+                }
+            }
+        }
+
+        moved[axis] = if amount > 0.0 {
+            collider.min[axis] - (after.max[axis] - moved[axis]) - COLLISION_SKIN
+        } else {
+            collider.max[axis] + (moved[axis] - after.min[axis]) + COLLISION_SKIN
+        };
+        blocked = true;
+        if !contacts.contains(&index) {
+            contacts.push(index);
+        }
+    }
+
+    *eye = moved;
+    blocked
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn walker() -> FreeCamera {
+        FreeCamera {
+            movement_mode: MovementMode::Walk,
+            ..default()
+        }
+    }
+
+    fn aabb(min: Vec3, max: Vec3) -> Aabb3d {
+        Aabb3d {
+            min: min.into(),
+            max: max.into(),
+        }
+    }
+
+    /// Flying input with the default basis, -Z forward, and no colliders.
+    fn flying(axis: Vec3) -> MovementInput<'static> {
+        MovementInput {
+            axis,
+            run: false,
+            walking: false,
+            right: Vec3::X,
+            up: Vec3::Y,
+            forward: Vec3::NEG_Z,
+            translation: Vec3::ZERO,
+            colliders: &[],
+        }
+    }
+
+    #[test]
+    fn tap_within_the_input_buffer_still_moves() {
+        let config = FreeCamera {
+            input_buffer_ms: 50.0,
+            ..default()
+        };
+        let mut state = FreeCameraState::default();
+        state
+            .buffered_presses
+            .push((Action::MoveForward, Duration::from_millis(100)));
+
+        // The key was already released again by the next tick, 10 ms later
+        state.prune_buffered_presses(&config, Duration::from_millis(110));
+        let axis = movement_axes(|action| state.is_buffered(action));
+        assert_eq!(axis, Vec3::Z);
+
+        let step = integrate_camera(&state, &flying(axis), &config, DT);
+        assert!(step.translation.z < 0.0);
+    }
+
+    #[test]
+    fn taps_older_than_the_input_buffer_are_dropped() {
+        let config = FreeCamera {
+            input_buffer_ms: 50.0,
+            ..default()
+        };
+        let mut state = FreeCameraState::default();
+        state
+            .buffered_presses
+            .push((Action::MoveForward, Duration::from_millis(100)));
+
+        state.prune_buffered_presses(&config, Duration::from_millis(200));
+        assert!(!state.is_buffered(Action::MoveForward));
+        assert_eq!(
+            movement_axes(|action| state.is_buffered(action)),
+            Vec3::ZERO
+        );
+    }
+
+    #[test]
+    fn friction_decays_velocity_without_input() {
+        let config = FreeCamera::default();
+        let state = FreeCameraState {
+            velocity: Vec3::new(0.0, 0.0, 4.0),
+            ..default()
+        };
+
+        let step = integrate_camera(&state, &flying(Vec3::ZERO), &config, DT);
+        let expected = 4.0 * (-config.friction * DT).exp();
+        assert!((step.velocity.z - expected).abs() < 1e-4);
+        assert_eq!(step.velocity.x, 0.0);
+        assert_eq!(step.velocity.y, 0.0);
+    }
+
+    #[test]
+    fn forward_input_moves_at_walk_and_run_speed() {
+        let config = FreeCamera::default();
+        let state = FreeCameraState::default();
+
+        let step = integrate_camera(&state, &flying(Vec3::Z), &config, DT);
+        assert_eq!(step.velocity, Vec3::Z * config.walk_speed);
+        assert!((step.translation - Vec3::NEG_Z * config.walk_speed * DT).length() < 1e-5);
+
+        let running = MovementInput {
+            run: true,
+            ..flying(Vec3::Z)
+        };
+        let step = integrate_camera(&state, &running, &config, DT);
+        assert!(step.running);
+        assert_eq!(step.velocity, Vec3::Z * config.run_speed);
+    }
+
+    #[test]
+    fn forward_input_moves_in_a_straight_line() {
+        let config = FreeCamera::default();
+        let mut state = FreeCameraState::default();
+        let mut translation = Vec3::ZERO;
+        for _ in 0..60 {
+            let input = MovementInput {
+                translation,
+                ..flying(Vec3::Z)
+            };
+            let step = integrate_camera(&state, &input, &config, DT);
+            translation = step.translation;
+            state.velocity = step.velocity;
+        }
+
+        assert!(translation.x.abs() < 1e-5 && translation.y.abs() < 1e-5);
+        assert!((translation.z + config.walk_speed).abs() < 1e-3);
+    }
+
+    #[test]
+    fn max_speed_clamps_the_velocity() {
+        let config = FreeCamera {
+            max_speed: Some(2.0),
+            ..default()
+        };
+        let input = MovementInput {
+            run: true,
+            ..flying(Vec3::new(1.0, 0.0, 1.0))
+        };
+
+        let step = integrate_camera(&FreeCameraState::default(), &input, &config, DT);
+        assert!((step.velocity.length() - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn air_control_limits_steering() {
+        let config = FreeCamera {
+            movement_mode: MovementMode::Walk,
+            ..default()
+        };
+        let input = MovementInput {
+            walking: true,
+            ..flying(Vec3::Z)
+        };
+
+        let airborne = integrate_camera(&FreeCameraState::default(), &input, &config, DT);
+        assert!(airborne.velocity.z > 0.0);
+        assert!(airborne.velocity.z < config.walk_speed * config.air_control * 2.0);
+
+        let grounded = FreeCameraState {
+            grounded: true,
+            ..default()
+        };
+        let step = integrate_camera(&grounded, &input, &config, DT);
+        assert_eq!(step.velocity.z, config.walk_speed);
+    }
+
+    #[test]
+    fn zero_dt_does_not_move_the_camera() {
+        let config = FreeCamera {
+            movement_mode: MovementMode::Walk,
+            ..default()
+        };
+        let state = FreeCameraState {
+            velocity: Vec3::new(1.0, -3.0, 2.0),
+            ..default()
+        };
+        let input = MovementInput {
+            walking: true,
+            translation: Vec3::new(1.0, 2.0, 3.0),
+            ..flying(Vec3::Z)
+        };
+
+        let step = integrate_camera(&state, &input, &config, 0.0);
+        assert_eq!(step.translation, input.translation);
+    }
+
+    #[test]
+    fn huge_mouse_delta_is_clamped_to_max_look_delta() {
+        let config = FreeCamera::default();
+
+        let clamped = config.clamp_look_delta(Vec2::new(30_000.0, -40_000.0));
+        assert!((clamped.length() - config.max_look_delta).abs() < 1e-3);
+        assert!(clamped.normalize().abs_diff_eq(Vec2::new(0.6, -0.8), 1e-6));
+
+        let small = Vec2::new(3.0, 4.0);
+        assert_eq!(config.clamp_look_delta(small), small);
+    }
+
+    #[test]
+    fn walking_steps_up_onto_a_low_box() {
+        let config = walker();
+        let body = CollisionBody::new(&config, 0.0);
+        let step = aabb(Vec3::new(1.0, 0.0, -1.0), Vec3::new(3.0, 0.2, 1.0));
+        // Feet on the ground at y = 0
+        let mut eye = Vec3::new(0.0, config.stand_height, 0.0);
+
+        let blocked = move_and_collide(
+            &mut eye,
+            Vec3::X * 2.0,
+            body,
+            &[step],
+            config.step_height,
+            &mut Vec::new(),
+        );
+        assert!(!blocked.x);
+        assert!((eye.x - 2.0).abs() < 1e-5);
+        assert!((eye.y - (config.stand_height + 0.2)).abs() < 0.01);
+    }
+
+    #[test]
+    fn walking_is_stopped_by_a_box_taller_than_the_step_height() {
+        let config = walker();
+        let body = CollisionBody::new(&config, 0.0);
+        let wall = aabb(Vec3::new(1.0, 0.0, -1.0), Vec3::new(3.0, 1.0, 1.0));
+        let mut eye = Vec3::new(0.0, config.stand_height, 0.0);
+
+        let blocked = move_and_collide(
+            &mut eye,
+            Vec3::X * 2.0,
+            body,
+            &[wall],
+            config.step_height,
+            &mut Vec::new(),
+        );
+        assert!(blocked.x);
+        assert!(eye.x < 1.0 - config.collision_radius + 1e-3);
+        assert_eq!(eye.y, config.stand_height);
+    }
+
+    #[test]
+    fn max_speed_clamps_a_huge_impulse() {
+        let config = FreeCamera {
+            max_speed: Some(1.0),
+            ..default()
+        };
+        let state = FreeCameraState {
+            velocity: Vec3::new(1_000.0, 0.0, -5_000.0),
+            ..default()
+        };
+
+        let step = integrate_camera(&state, &flying(Vec3::ZERO), &config, DT);
+        assert!(step.velocity.length() <= 1.0 + 1e-5);
+    }
+
+    #[test]
+    fn movement_basis_directions_for_a_turned_and_tilted_camera() {
+        // Turned a quarter to the left and looking down at 45°
+        let (yaw, pitch) = (FRAC_PI_2, -FRAC_PI_4);
+        let rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+        let close = |a: Vec3, b: Vec3| a.abs_diff_eq(b, 1e-5);
+
+        let (forward, right) = MovementBasis::CameraRelative.axes(rotation, yaw, Quat::IDENTITY);
+        assert!(close(
+            forward,
+            Vec3::new(-FRAC_1_SQRT_2, -FRAC_1_SQRT_2, 0.0)
+        ));
+        assert!(close(right, Vec3::NEG_Z));
+
+        let (forward, right) = MovementBasis::YawRelative.axes(rotation, yaw, Quat::IDENTITY);
+        assert!(close(forward, Vec3::NEG_X));
+        assert!(close(right, Vec3::NEG_Z));
+
+        let (forward, right) = MovementBasis::WorldAxes.axes(rotation, yaw, Quat::IDENTITY);
+        assert!(close(forward, Vec3::NEG_Z));
+        assert!(close(right, Vec3::X));
+    }
+
+    #[test]
+    fn horizontal_movement_stays_on_a_tilted_ground_plane() {
+        // Gravity pulls along -X, so the ground plane is YZ
+        let config = FreeCamera {
+            up: Vec3::X,
+            ..default()
+        };
+        let up_rotation = config.up_rotation();
+        let up = up_rotation * Vec3::Y;
+        assert!(up.abs_diff_eq(Vec3::X, 1e-6));
+
+        for yaw in [0.0, 0.7, FRAC_PI_2, 2.5] {
+            let rotation = config.look_rotation(yaw, -0.4);
+            let (forward, right) = MovementBasis::YawRelative.axes(rotation, yaw, up_rotation);
+            let input = MovementInput {
+                right,
+                up,
+                forward,
+                ..flying(Vec3::new(1.0, 0.0, 1.0))
+            };
+
+            let step = integrate_camera(&FreeCameraState::default(), &input, &config, DT);
+            assert!(step.translation.length() > 0.0);
+            assert!(step.translation.x.abs() < 1e-5, "yaw {yaw}");
+        }
+    }
+
+    #[test]
+    fn interpolated_transform_blends_the_last_two_ticks() {
+        let previous = Transform::from_xyz(0.0, 1.0, 0.0);
+        let current = Transform::from_xyz(2.0, 1.0, -4.0)
+            .with_rotation(Quat::from_rotation_y(FRAC_PI_2))
+            .with_scale(Vec3::splat(3.0));
+
+        assert_eq!(interpolate_transform(&previous, &current, 0.0), previous);
+        let end = interpolate_transform(&previous, &current, 1.0);
+        assert!(end.translation.abs_diff_eq(current.translation, 1e-6));
+        assert!(end.rotation.abs_diff_eq(current.rotation, 1e-6));
+
+        // A 144 Hz frame landing 40% of the way into a 60 Hz tick
+        let blended = interpolate_transform(&previous, &current, 0.4);
+        assert!(
+            blended
+                .translation
+                .abs_diff_eq(Vec3::new(0.8, 1.0, -1.6), 1e-6)
+        );
+        assert!(
+            blended
+                .rotation
+                .abs_diff_eq(Quat::from_rotation_y(0.4 * FRAC_PI_2), 1e-6)
+        );
+        assert!(blended.scale.abs_diff_eq(Vec3::splat(1.8), 1e-6));
+    }
+
+    #[test]
+    fn teleports_outside_fixed_update_are_not_interpolated() {
+        let tick = Transform::from_xyz(1.0, 0.0, 0.0);
+        let mut previous = PreviousTransform {
+            transform: Transform::IDENTITY,
+            current: tick,
+            written: tick,
+        };
+
+        let teleported = Transform::from_xyz(50.0, 0.0, 0.0);
+        previous.sync(&teleported);
+        assert_eq!(previous.transform, teleported);
+        assert_eq!(previous.current, teleported);
+
+        // Writing back what the systems wrote keeps the blend
+        let mut untouched = PreviousTransform {
+            transform: Transform::IDENTITY,
+            current: tick,
+            written: tick,
+        };
+        untouched.sync(&tick);
+        assert_eq!(untouched.transform, Transform::IDENTITY);
+    }
+
+    #[test]
+    fn colliders_off_the_collision_mask_are_passed_through() {
+        let config = FreeCamera {
+            collision_mask: CollisionLayer(0b01),
+            ..default()
+        };
+        let glass = (
+            CameraCollider {
+                layers: CollisionLayer(0b10),
+            },
+            aabb(Vec3::new(2.0, -1.0, -1.0), Vec3::new(2.2, 1.0, 1.0)),
+        );
+        let wall = (
+            CameraCollider {
+                layers: CollisionLayer(0b01),
+            },
+            aabb(Vec3::new(5.0, -1.0, -1.0), Vec3::new(5.2, 1.0, 1.0)),
+        );
+        let colliders: Vec<Aabb3d> = [glass, wall]
+            .into_iter()
+            .filter(|(collider, _)| collider.blocks(&config))
+            .map(|(_, bounds)| bounds)
+            .collect();
+
+        let mut eye = Vec3::ZERO;
+        let blocked = move_and_collide(
+            &mut eye,
+            Vec3::X * 10.0,
+            CollisionBody::new(&config, 0.0),
+            &colliders,
+            0.0,
+            &mut Vec::new(),
+        );
+        assert!(blocked.x);
+        // Through the glass and up against the wall
+        assert!(eye.x > 2.2);
+        assert!((eye.x - (5.0 - config.collision_radius)).abs() < 0.01);
+
+        let no_collisions = FreeCamera {
+            collisions: false,
+            ..config
+        };
+        assert!(!wall.0.blocks(&no_collisions));
+    }
+
+    #[test]
+    fn height_clamps() {
+        let config = FreeCamera {
+            min_height: Some(1.0),
+            max_height: Some(10.0),
+            ..default()
+        };
+        assert_eq!(config.clamp_height(-5.0, 0.0), 1.0);
+        assert_eq!(config.clamp_height(5.0, 0.0), 5.0);
+        assert_eq!(config.clamp_height(50.0, 0.0), 10.0);
+
+        let unbounded = FreeCamera::default();
+        assert_eq!(unbounded.clamp_height(-500.0, 0.0), -500.0);
+        assert_eq!(unbounded.clamp_height(500.0, 0.0), 500.0);
+    }
+
+    #[test]
+    fn ceiling_wins_when_height_clamps_cross() {
+        let config = FreeCamera {
+            min_height: Some(10.0),
+            max_height: Some(2.0),
+            ..default()
+        };
+        assert_eq!(config.clamp_height(0.0, 0.0), 2.0);
+        assert_eq!(config.clamp_height(5.0, 0.0), 2.0);
+        assert_eq!(config.clamp_height(20.0, 0.0), 2.0);
+    }
+
+    #[test]
+    fn remapped_key_drives_the_action() {
+        let mut input_map = InputMap::empty();
+        input_map
+            .bind(Action::MoveForward, KeyCode::KeyI)
+            .bind(Action::MoveForward, KeyCode::ArrowUp)
+            .bind(Action::MoveForward, KeyCode::KeyI);
+        assert_eq!(
+            input_map.bindings(Action::MoveForward),
+            [KeyCode::KeyI.into(), KeyCode::ArrowUp.into()]
+        );
+        assert_eq!(input_map.describe(Action::MoveForward), "KeyI/ArrowUp");
+        assert_eq!(input_map.describe(Action::MoveBack), "Unbound");
+
+        let mut world = World::new();
+        let mut keys = ButtonInput::<KeyCode>::default();
+        keys.press(KeyCode::KeyI);
+        keys.press(KeyCode::KeyW);
+        world.insert_resource(input_map);
+        world.insert_resource(keys);
+        world.init_resource::<ButtonInput<MouseButton>>();
+
+        let (forward, back) = world
+            .run_system_once(|actions: ActionInput<Action>| {
+                (
+                    actions.pressed(Action::MoveForward),
+                    actions.pressed(Action::MoveBack),
+                )
+            })
+            .unwrap();
+        assert!(forward);
+        // KeyW is held but no longer bound to anything
+        assert!(!back);
+    }
+
+    #[test]
+    fn settings_readout_strings() {
+        let config = FreeCamera {
+            walk_speed: 5.0,
+            run_speed: 15.0,
+            friction: 40.0,
+            inertia: true,
+            scroll_factor: 0.5,
+            invert_scroll: false,
+            nudge_step: 0.25,
+            proximity_speed_scaling: false,
+            ..default()
+        };
+        let mut state = FreeCameraState {
+            speed_multiplier: 2.0,
+            velocity: Vec3::new(3.0, 4.0, 0.0),
+            ..default()
+        };
+        assert_eq!(
+            config.fmt_speeds(&state),
+            "Walk Speed: 5.00 x2.00 = 10.00\nRun Speed: 15.00 x2.00 = 30.00"
+        );
+        assert_eq!(config.fmt_friction(), "Friction: 40.0, inertia: true");
+        assert_eq!(
+            config.fmt_scroll(),
+            "Scroll factor: 0.50\nInvert scroll: false"
+        );
+        assert_eq!(config.fmt_nudge(), "Nudge step: 0.250");
+        assert_eq!(config.fmt_proximity_speed(&state), "Proximity speed: off");
+        assert_eq!(state.fmt_velocity(), "Speed: 5.00");
+
+        assert_eq!(state.fmt_last_scroll(), "Last scroll: -");
+        state.last_scroll = Some((ScrollTarget::Fov, 90f32.to_radians()));
+        assert_eq!(state.fmt_last_scroll(), "Last scroll: FOV 90.0°");
+    }
+
+    #[test]
+    fn controls_help_lists_the_bindings() {
+        let mut input_map = InputMap::default();
+        input_map.bind(Action::Run, KeyCode::KeyR);
+        let help = input_map.to_string();
+        assert!(help.starts_with("\nFreecam Controls:\n"));
+        assert!(help.contains("    KeyW & KeyS\t- Fly forward & backwards"));
+        assert!(help.contains("    ShiftLeft/KeyR\t- Fly faster while held"));
+    }
+
+    #[test]
+    fn mouse_turn_is_clamped_to_max_rotation_per_frame() {
+        let config = FreeCamera {
+            sensitivity: 1.0,
+            max_rotation_per_frame: FRAC_PI_2,
+            ..default()
+        };
+
+        let turn = config.mouse_turn(Vec2::new(0.0, 1e6));
+        assert!((turn.length() - FRAC_PI_2).abs() < 1e-5);
+        assert!(turn.normalize().abs_diff_eq(Vec2::Y, 1e-6));
+
+        let small = config.mouse_turn(Vec2::new(9.0, 0.0));
+        assert!((small.x - 9.0 * RADIANS_PER_DOT).abs() < 1e-6);
+
+        let unclamped = FreeCamera {
+            max_rotation_per_frame: f32::INFINITY,
+            ..config
+        };
+        assert!(unclamped.mouse_turn(Vec2::new(0.0, 1e6)).y > 1000.0);
+    }
+
+    #[test]
+    fn pressing_forward_moves_where_the_camera_faces() {
+        let config = FreeCamera::default();
+        let yaw = FRAC_PI_2;
+        let rotation = config.look_rotation(yaw, 0.0);
+
+        let axis = movement_axes(|action| action == Action::MoveForward);
+        assert_eq!(axis, Vec3::Z);
+
+        let (right, up, forward) = config.movement_frame(rotation, yaw, false);
+        let direction = (axis.x * right + axis.y * up + axis.z * forward).normalize_or_zero();
+        // A quarter turn left faces -X
+        assert!(direction.abs_diff_eq(Vec3::NEG_X, 1e-5));
+
+        // Opposite keys cancel out
+        let cancelled =
+            movement_axes(|action| matches!(action, Action::MoveForward | Action::MoveBack));
+        assert_eq!(cancelled, Vec3::ZERO);
+    }
+
+    #[test]
+    fn horizontal_speed_lasts_longer_in_the_air() {
+        let config = walker();
+        let input = MovementInput {
+            walking: true,
+            ..flying(Vec3::ZERO)
+        };
+        let moving = |grounded| FreeCameraState {
+            velocity: Vec3::new(0.0, 0.0, 4.0),
+            grounded,
+            ..default()
+        };
+
+        let on_ground = integrate_camera(&moving(true), &input, &config, DT);
+        let in_air = integrate_camera(&moving(false), &input, &config, DT);
+        assert!((on_ground.velocity.z - 4.0 * (-config.friction * DT).exp()).abs() < 1e-4);
+        assert!((in_air.velocity.z - 4.0 * (-config.air_friction * DT).exp()).abs() < 1e-4);
+        assert!(in_air.velocity.z > on_ground.velocity.z);
+    }
+
+    #[test]
+    fn flying_up_follows_world_up_while_looking_down() {
+        let config = FreeCamera::default();
+        let rotation = config.look_rotation(0.0, -FRAC_PI_4);
+
+        let (_, up, _) = config.movement_frame(rotation, 0.0, false);
+        assert!(up.abs_diff_eq(Vec3::Y, 1e-6));
+
+        let camera_up = FreeCamera {
+            fly_vertical_world_up: false,
+            ..config
+        };
+        let (_, up, _) = camera_up.movement_frame(rotation, 0.0, false);
+        assert!(up.abs_diff_eq(rotation * Vec3::Y, 1e-6));
+        // Leans forward, towards -Z, when looking down
+        assert!(up.z < -0.5);
+
+        // Walking jumps along world up either way
+        let (_, up, _) = camera_up.movement_frame(rotation, 0.0, true);
+        assert!(up.abs_diff_eq(Vec3::Y, 1e-6));
+    }
+
+    #[test]
+    fn falls_never_exceed_terminal_velocity() {
+        let config = walker();
+        let mut state = FreeCameraState::default();
+        let mut translation = Vec3::new(0.0, 10_000.0, 0.0);
+
+        for _ in 0..60 * 30 {
+            let input = MovementInput {
+                walking: true,
+                translation,
+                ..flying(Vec3::ZERO)
+            };
+            let step = integrate_camera(&state, &input, &config, DT);
+            assert!(step.velocity.y >= -config.terminal_velocity);
+            assert!(!step.grounded);
+            translation = step.translation;
+            state.velocity = step.velocity;
+        }
+        // Thirty seconds of falling is long enough to reach it
+        assert_eq!(state.velocity.y, -config.terminal_velocity);
+    }
+
+    #[test]
+    fn floor_clamp_holds_the_feet() {
+        let config = FreeCamera {
+            min_height: Some(0.0),
+            ..walker()
+        };
+        let state = FreeCameraState {
+            eye_height: config.eye_height(0.0),
+            ..default()
+        };
+
+        let height = config.clamp_height(-3.0, state.eye_height);
+        assert_eq!(height, config.stand_height);
+        let feet = state.feet_position(&config, Vec3::new(2.0, height, 1.0));
+        assert_eq!(feet, Vec3::new(2.0, 0.0, 1.0));
+
+        // Crouching lowers the camera, the feet stay on the floor
+        let crouched = config.eye_height(1.0);
+        assert_eq!(crouched, config.crouch_height);
+        assert_eq!(config.clamp_height(-3.0, crouched), config.crouch_height);
+    }
+
+    #[test]
+    fn set_look_rotation_round_trips_yaw_and_pitch() {
+        for up in [Vec3::Y, Vec3::X] {
+            let config = FreeCamera { up, ..default() };
+            let mut state = FreeCameraState::default();
+            state.snap_yaw(FRAC_PI_4);
+
+            state.set_look_rotation(&config, config.look_rotation(1.0, -0.5));
+            assert!((state.yaw - 1.0).abs() < 1e-5, "{up}");
+            assert!((state.pitch + 0.5).abs() < 1e-5, "{up}");
+            assert!(
+                state
+                    .look_rotation(&config)
+                    .abs_diff_eq(config.look_rotation(1.0, -0.5), 1e-5)
+            );
+            // Setting the rotation cancels the snap turn
+            assert!(state.look_tween.is_none());
+        }
+    }
+
+    #[test]
+    fn set_look_rotation_drops_roll_and_clamps_pitch() {
+        let config = FreeCamera::default();
+        let mut state = FreeCameraState::default();
+
+        let rolled = config.look_rotation(0.3, 0.2) * Quat::from_rotation_z(0.7);
+        state.set_look_rotation(&config, rolled);
+        assert!((state.yaw - 0.3).abs() < 1e-5);
+        assert!((state.pitch - 0.2).abs() < 1e-5);
+
+        state.set_look_rotation(&config, Quat::from_rotation_x(FRAC_PI_2));
+        assert!(state.pitch <= FRAC_PI_2);
+        assert!((state.pitch - FRAC_PI_2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn holding_look_up_pitches_up_to_the_clamp() {
+        let config = FreeCamera::default();
+        let mut state = FreeCameraState::default();
+
+        let axes = look_axes(|action| action == Action::LookUp);
+        assert_eq!(axes, Vec2::NEG_Y);
+
+        let mut last_pitch = state.pitch;
+        for _ in 0..60 * 10 {
+            state.turn(axes * config.keyboard_look_speed * DT);
+            assert!(state.pitch >= last_pitch);
+            last_pitch = state.pitch;
+        }
+        assert_eq!(state.pitch, FRAC_PI_2);
+        assert_eq!(state.yaw, 0.0);
+
+        state.turn(look_axes(|action| action == Action::LookRight));
+        assert_eq!(state.yaw, -1.0);
+    }
+
+    #[test]
+    fn gamepad_look_deadzone_and_curve() {
+        let config = FreeCamera {
+            gamepad_look_deadzone: 0.2,
+            gamepad_look_exponent: 2.0,
+            ..default()
+        };
+        assert_eq!(config.shape_gamepad_look(Vec2::ZERO), Vec2::ZERO);
+        assert_eq!(config.shape_gamepad_look(Vec2::new(0.1, 0.1)), Vec2::ZERO);
+        assert_eq!(config.shape_gamepad_look(Vec2::new(0.0, -0.2)), Vec2::ZERO);
+
+        let full = config.shape_gamepad_look(Vec2::X);
+        assert!(full.abs_diff_eq(Vec2::X, 1e-6));
+        // Keeps the direction of the stick
+        let diagonal = config.shape_gamepad_look(Vec2::new(0.6, -0.6));
+        assert!(
+            diagonal
+                .normalize()
+                .abs_diff_eq(Vec2::new(1.0, -1.0).normalize(), 1e-6)
+        );
+
+        let mut last = 0.0;
+        for i in 0..=100 {
+            let length = config
+                .shape_gamepad_look(Vec2::Y * (i as f32 / 100.0))
+                .length();
+            assert!(length >= last);
+            assert!(length <= 1.0);
+            last = length;
+        }
+    }
+
+    #[test]
+    fn cast_ray_hits_the_nearest_face() {
+        let near = Entity::from_raw_u32(1).unwrap();
+        let far = Entity::from_raw_u32(2).unwrap();
+        let around = Entity::from_raw_u32(3).unwrap();
+        let colliders = [
+            (
+                far,
+                aabb(Vec3::new(-1.0, -1.0, -9.0), Vec3::new(1.0, 1.0, -8.0)),
+            ),
+            (
+                near,
+                aabb(Vec3::new(-1.0, -1.0, -5.0), Vec3::new(1.0, 1.0, -4.0)),
+            ),
+            // Contains the origin, so it is skipped
+            (around, aabb(Vec3::splat(-0.5), Vec3::splat(0.5))),
+        ];
+
+        let hit = cast_ray(Vec3::ZERO, Dir3::NEG_Z, colliders).unwrap();
+        assert_eq!(hit.entity, near);
+        assert!((hit.distance - 4.0).abs() < 1e-5);
+        assert!(hit.point.abs_diff_eq(Vec3::new(0.0, 0.0, -4.0), 1e-5));
+        assert_eq!(hit.normal, Dir3::Z);
+
+        assert!(cast_ray(Vec3::ZERO, Dir3::X, colliders).is_none());
+    }
+
+    #[test]
+    fn face_normals_point_out_of_the_face_hit() {
+        let bounds = aabb(Vec3::new(0.0, 0.0, 0.0), Vec3::new(4.0, 2.0, 1.0));
+        assert_eq!(face_normal(&bounds, Vec3::new(4.0, 1.0, 0.5)), Dir3::X);
+        assert_eq!(face_normal(&bounds, Vec3::new(0.0, 1.0, 0.5)), Dir3::NEG_X);
+        assert_eq!(face_normal(&bounds, Vec3::new(2.0, 2.0, 0.5)), Dir3::Y);
+        assert_eq!(face_normal(&bounds, Vec3::new(2.0, 0.0, 0.5)), Dir3::NEG_Y);
+        assert_eq!(face_normal(&bounds, Vec3::new(2.0, 1.0, 1.0)), Dir3::Z);
+        assert_eq!(face_normal(&bounds, Vec3::new(2.0, 1.0, 0.0)), Dir3::NEG_Z);
+    }
+
+    #[test]
+    fn without_inertia_releasing_the_keys_stops_the_camera() {
+        let config = FreeCamera {
+            inertia: false,
+            ..default()
+        };
+        let state = FreeCameraState {
+            velocity: Vec3::new(0.0, 0.0, 4.0),
+            ..default()
+        };
+
+        let step = integrate_camera(&state, &flying(Vec3::ZERO), &config, DT);
+        assert_eq!(step.velocity, Vec3::ZERO);
+        assert_eq!(step.translation, Vec3::ZERO);
+
+        let step = integrate_camera(&state, &flying(Vec3::X), &config, DT);
+        assert_eq!(step.velocity, Vec3::X * config.walk_speed);
+    }
+
+    /// Flies diagonally into a wall along Z and returns the speed of each frame after the
+    /// camera touches it.
+    fn speeds_along_wall(slide_smoothing: f32) -> Vec<f32> {
+        let config = FreeCamera {
+            slide_smoothing,
+            ..default()
+        };
+        let wall = [aabb(Vec3::new(1.0, -5.0, -50.0), Vec3::new(2.0, 5.0, 5.0))];
+        let mut state = FreeCameraState::default();
+        let mut translation = Vec3::ZERO;
+        let mut speeds = Vec::new();
+        for _ in 0..120 {
+            let input = MovementInput {
+                translation,
+                colliders: &wall,
+                ..flying(Vec3::new(1.0, 0.0, 1.0))
+            };
+            let step = integrate_camera(&state, &input, &config, DT);
+            if state.sliding {
+                speeds.push((step.translation - translation).length() / DT);
+            }
+            translation = step.translation;
+            state.velocity = step.velocity;
+            state.slide_velocity = step.slide_velocity;
+            state.sliding = step.sliding;
+        }
+        speeds
+    }
+
+    #[test]
+    fn slide_smoothing_keeps_the_speed_when_grazing_a_wall() {
+        let speed = FreeCamera::default().walk_speed;
+        let along_wall = speed * FRAC_1_SQRT_2;
+
+        let smoothed = speeds_along_wall(0.2);
+        assert!(!smoothed.is_empty());
+        assert!((smoothed[0] - speed).abs() < 0.2 * speed);
+        for pair in smoothed.windows(2) {
+            assert!(pair[1] <= pair[0] + 1e-3);
+            assert!(pair[0] - pair[1] < 0.1 * speed);
+        }
+        assert!((smoothed.last().unwrap() - along_wall).abs() < 0.01);
+
+        // Without smoothing the wall takes the speed away at once
+        let projected = speeds_along_wall(0.0);
+        assert!((projected[0] - along_wall).abs() < 1e-3);
+    }
+
+    #[test]
+    fn basis_vectors_are_orthonormal() {
+        for up in [Vec3::Y, Vec3::new(1.0, 1.0, 0.0)] {
+            let config = FreeCamera { up, ..default() };
+            for (yaw, pitch) in [(0.0, 0.0), (1.0, -0.5), (-2.5, 1.2), (PI, FRAC_PI_2)] {
+                let state = FreeCameraState {
+                    yaw,
+                    pitch,
+                    ..default()
+                };
+                let right = *state.right(&config);
+                let up = *state.up(&config);
+                let forward = *state.forward(&config);
+                assert!(right.dot(up).abs() < 1e-5);
+                assert!(right.dot(forward).abs() < 1e-5);
+                assert!(up.dot(forward).abs() < 1e-5);
+                // Right-handed, with the camera looking along -Z
+                assert!(right.cross(up).abs_diff_eq(-forward, 1e-5));
+            }
+        }
+
+        let state = FreeCameraState::default();
+        let config = FreeCamera::default();
+        assert_eq!(state.right(&config), Dir3::X);
+        assert_eq!(state.up(&config), Dir3::Y);
+        assert_eq!(state.forward(&config), Dir3::NEG_Z);
+    }
+
+    #[test]
+    fn cursor_modes_set_the_grab_mode_and_visibility() {
+        assert_eq!(
+            CursorMode::GrabbedHidden.grabbed_cursor(),
+            (CursorGrabMode::Locked, false)
+        );
+        assert_eq!(
+            CursorMode::ConfinedVisible.grabbed_cursor(),
+            (CursorGrabMode::Confined, true)
+        );
+        assert_eq!(
+            CursorMode::FreeVisible.grabbed_cursor(),
+            (CursorGrabMode::None, true)
+        );
+    }
+
+    #[test]
+    fn changing_the_cursor_mode_regrabs_the_cursor() {
+        let mut grab = CursorGrab::default();
+        let target = grab.target_mode(true, CursorMode::ConfinedVisible);
+        assert_eq!(target, None);
+        assert!(!grab.mode_changed(target));
+
+        grab.toggled = true;
+        grab.mode = Some(CursorMode::GrabbedHidden);
+        assert!(!grab.mode_changed(grab.target_mode(true, CursorMode::GrabbedHidden)));
+        assert!(grab.mode_changed(grab.target_mode(true, CursorMode::ConfinedVisible)));
+
+        grab.toggled = false;
+        assert!(!grab.is_grabbed());
+        assert_eq!(grab.target_mode(true, CursorMode::ConfinedVisible), None);
+    }
+
+    #[test]
+    fn disabling_the_cursor_grab_releases_and_restores_it() {
+        let mut grab = CursorGrab {
+            toggled: true,
+            mode: Some(CursorMode::GrabbedHidden),
+            ..default()
+        };
+
+        let disabled = grab.target_mode(false, CursorMode::GrabbedHidden);
+        assert_eq!(disabled, None);
+        assert!(grab.mode_changed(disabled));
+        grab.mode = disabled;
+        // Still toggled on, so turning grabbing back on grabs the cursor again
+        assert!(grab.is_grabbed());
+        let enabled = grab.target_mode(true, CursorMode::GrabbedHidden);
+        assert_eq!(enabled, Some(CursorMode::GrabbedHidden));
+        assert!(grab.mode_changed(enabled));
+    }
+
+    #[test]
+    fn pitch_speed_factor_falls_off_towards_straight_up_and_down() {
+        let config = FreeCamera {
+            pitch_speed_falloff: 0.4,
+            ..default()
+        };
+        assert_eq!(config.pitch_speed_factor(0.0), 1.0);
+        assert!((config.pitch_speed_factor(FRAC_PI_2) - 0.6).abs() < 1e-6);
+        assert!((config.pitch_speed_factor(-FRAC_PI_2) - 0.6).abs() < 1e-6);
+        assert_eq!(
+            config.pitch_speed_factor(0.5),
+            config.pitch_speed_factor(-0.5)
+        );
+        assert!(config.pitch_speed_factor(0.5) > config.pitch_speed_factor(1.0));
+
+        // Out of range falloffs are clamped
+        let full = FreeCamera {
+            pitch_speed_falloff: 2.0,
+            ..default()
+        };
+        assert_eq!(full.pitch_speed_factor(FRAC_PI_2), 0.0);
+        let off = FreeCamera {
+            pitch_speed_falloff: 0.0,
+            ..default()
+        };
+        assert_eq!(off.pitch_speed_factor(FRAC_PI_2), 1.0);
+    }
+
+    #[test]
+    fn spring_arm_stops_just_in_front_of_a_wall() {
+        let arm = SpringArm::new(0.5);
+        let radius = 0.25;
+        // Looking at the origin from +Z, with a wall 4 behind the focus
+        let wall = [(
+            Entity::PLACEHOLDER,
+            aabb(Vec3::new(-5.0, -5.0, 4.0), Vec3::new(5.0, 5.0, 5.0)),
+        )];
+        let hit = cast_ray(Vec3::ZERO, Dir3::Z, wall);
+
+        assert_eq!(arm.fit_length(10.0, hit, radius), 4.0 - radius);
+        // Nothing in the way within the focus distance
+        assert_eq!(arm.fit_length(3.0, hit, radius), 3.0);
+        assert_eq!(arm.fit_length(10.0, None, radius), 10.0);
+
+        // Never closer than the minimum length
+        let close = [(
+            Entity::PLACEHOLDER,
+            aabb(Vec3::new(-5.0, -5.0, 0.3), Vec3::new(5.0, 5.0, 1.0)),
+        )];
+        let hit = cast_ray(Vec3::ZERO, Dir3::Z, close);
+        assert_eq!(arm.fit_length(10.0, hit, radius), arm.min_length);
+    }
+
+    #[test]
+    fn falling_below_the_kill_plane_returns_to_the_ground() {
+        let config = FreeCamera {
+            kill_plane: Some(-10.0),
+            ..walker()
+        };
+        let mut state = FreeCameraState {
+            grounded: true,
+            ..default()
+        };
+        let ground = Vec3::new(3.0, 1.7, -2.0);
+        let mut transform = Transform::from_translation(ground);
+        state.track_ground(&config, &mut transform, false);
+        assert_eq!(state.last_grounded_position, Some(ground));
+
+        // Walk off the edge and fall
+        state.grounded = false;
+        state.velocity = Vec3::new(1.0, -20.0, 0.0);
+        transform.translation = Vec3::new(6.0, -5.0, -2.0);
+        state.track_ground(&config, &mut transform, false);
+        assert_eq!(transform.translation, Vec3::new(6.0, -5.0, -2.0));
+        assert_eq!(state.last_grounded_position, Some(ground));
+
+        transform.translation.y = -11.0;
+        state.track_ground(&config, &mut transform, false);
+        assert_eq!(transform.translation, ground);
+        assert_eq!(state.velocity, Vec3::ZERO);
+    }
+
+    #[test]
+    fn return_to_ground_without_ground_stays_put() {
+        let mut state = FreeCameraState::default();
+        let mut transform = Transform::from_xyz(0.0, -50.0, 0.0);
+        assert!(!state.return_to_ground(&mut transform));
+        assert_eq!(transform.translation, Vec3::new(0.0, -50.0, 0.0));
+    }
+
+    #[test]
+    fn scroll_steps_scale_both_speeds() {
+        let config = FreeCamera {
+            walk_speed: 5.0,
+            run_speed: 15.0,
+            scroll_factor: 0.5,
+            ..default()
+        };
+        let mut state = FreeCameraState::default();
+        assert_eq!(config.effective_speeds(&state), (5.0, 15.0));
+
+        assert_eq!(state.adjust_speed(&config, 2.0), 2.0);
+        // Steps add up from the target, before smoothing catches up
+        assert_eq!(state.adjust_speed(&config, 1.0), 2.5);
+        state.speed_multiplier = state.target_speed_multiplier;
+        assert_eq!(config.effective_speeds(&state), (12.5, 37.5));
+
+        // Never goes negative
+        assert_eq!(state.adjust_speed(&config, -100.0), 0.0);
+        state.speed_multiplier = state.target_speed_multiplier;
+        assert_eq!(config.effective_speeds(&state), (0.0, 0.0));
+    }
+
+    #[test]
+    fn only_fov_and_near_plane_scrolling_use_a_modifier() {
+        assert!(ScrollTarget::Fov.is_modified());
+        assert!(ScrollTarget::NearPlane.is_modified());
+        assert!(!ScrollTarget::Speed.is_modified());
+        assert!(!ScrollTarget::Zoom.is_modified());
+
+        let input_map = InputMap::default();
+        assert_eq!(input_map.describe(Action::ScrollFov), "ShiftLeft");
+        assert_eq!(input_map.describe(Action::ScrollNear), "ControlLeft");
+    }
+
+    #[test]
+    fn crouching_only_lowers_walking_cameras() {
+        let start = Vec3::new(0.0, 5.0, 0.0);
+        let mut state = FreeCameraState::default();
+        let mut translation = start;
+        for _ in 0..60 {
+            state.update_crouch(
+                &FreeCamera::default(),
+                &mut translation,
+                false,
+                1.0,
+                &[],
+                DT,
+            );
+        }
+        assert_eq!(translation, start);
+        assert_eq!(state.crouch, 0.0);
+        assert_eq!(state.eye_height, 0.0);
+
+        let config = walker();
+        for _ in 0..60 {
+            state.update_crouch(&config, &mut translation, true, 1.0, &[], DT);
+        }
+        assert_eq!(state.crouch, 1.0);
+        assert!(
+            (start.y - translation.y - (config.stand_height - config.crouch_height)).abs() < 1e-4
+        );
+    }
+
+    #[test]
+    fn crouch_speed_multiplier_only_applies_while_walking() {
+        let config = FreeCamera {
+            crouch_speed_multiplier: 0.5,
+            ..default()
+        };
+        let state = FreeCameraState {
+            crouch: 1.0,
+            ..default()
+        };
+        let step = integrate_camera(&state, &flying(Vec3::X), &config, DT);
+        assert_eq!(step.velocity, Vec3::X * config.walk_speed);
+    }
+
+    #[test]
+    fn feet_are_below_the_eye_along_the_up_direction() {
+        let state = FreeCameraState {
+            eye_height: 1.5,
+            ..default()
+        };
+        let eye = Vec3::new(2.0, 3.0, 1.0);
+
+        let upright = walker();
+        assert_eq!(state.feet_position(&upright, eye), Vec3::new(2.0, 1.5, 1.0));
+
+        // Walking on a wall, the feet are towards the wall rather than towards world -Y
+        let sideways = FreeCamera {
+            up: Vec3::X,
+            ..walker()
+        };
+        let feet = state.feet_position(&sideways, eye);
+        assert!(feet.abs_diff_eq(Vec3::new(0.5, 3.0, 1.0), 1e-6));
+    }
+}
@@ -1 +1,5 @@
+//! The camera controller and demo scene of the villa viewer, for use outside its binary.
 
+pub mod demo_world;
+pub mod easing;
+pub mod free_camera;
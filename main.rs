@@ -1,34 +1,282 @@
+mod console;
+
+use std::collections::{HashMap, HashSet};
 use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, FRAC_PI_6, FRAC_PI_8, PI};
+use std::fs::File;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 
 use bevy::prelude::*;
 use bevy::render::render_resource::{AddressMode, SamplerDescriptor};
 use bevy::{
-    camera_controller::free_camera::{FreeCamera, FreeCameraPlugin, FreeCameraState},
-    color::palettes::tailwind,
-    image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor},
-    math::Affine2,
+    anti_alias::{fxaa::Fxaa, taa::TemporalAntiAliasing},
+    asset::{RenderAssetUsages, io::file::FileAssetReader},
+    audio::Volume,
+    camera::{Exposure, RenderTarget, primitives::Aabb},
+    color::{Hue, palettes::tailwind},
+    core_pipeline::prepass::{DepthPrepass, MotionVectorPrepass},
+    diagnostic::{
+        Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, EntityCountDiagnosticsPlugin,
+        FrameTimeDiagnosticsPlugin, RegisterDiagnostic,
+    },
+    image::ImageSampler,
+    input::{
+        InputSystems,
+        keyboard::KeyboardInput,
+        mouse::{
+            AccumulatedMouseScroll, MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel,
+        },
+    },
+    light::{
+        CascadeShadowConfig, CascadeShadowConfigBuilder, DirectionalLightShadowMap, FogVolume,
+        NotShadowCaster, PointLightShadowMap, VolumetricFog,
+    },
+    post_process::{
+        auto_exposure::{AutoExposure, AutoExposurePlugin},
+        motion_blur::MotionBlur,
+    },
     prelude::*,
+    render::{
+        camera::{MipBias, TemporalJitter},
+        experimental::occlusion_culling::OcclusionCulling,
+        render_resource::{Extent3d, Face, TextureDimension, TextureFormat},
+        view::screenshot::{Screenshot, ScreenshotCaptured, save_to_disk},
+    },
+    ui::FocusPolicy,
+    window::{CursorGrabMode, CursorOptions, Monitor, PrimaryWindow, WindowResolution},
+};
+
+use serde::{Deserialize, Serialize};
+
+use villa_bevy::demo_world::{
+    AmbientSound, FLOOR_EMISSIVE, Landmark, Lod, SceneMaterial, SkyboxCircle, Trigger,
+    WallMaterial, WhiteMaterial, apply_loaded_textures, build_demo_world, face_direction,
+};
+use villa_bevy::easing::{EaseKind, apply_ease};
+use villa_bevy::free_camera::{
+    Action, CameraCollider, ControlScheme, FreeCamera, FreeCameraPlugin, FreeCameraState, InputMap,
+    LookAtTarget, MovementMode, SceneRaycast, SpringArm, collider_bounds,
 };
 
+use console::ConsolePlugin;
+
 fn main() {
+    let config = Config::load_or_default(Path::new(CONFIG_PATH));
+    let mut primary_window = Window::default();
+    if let Some(geometry) = &config.window {
+        geometry.apply(&mut primary_window);
+    }
+
     App::new()
-        .add_plugins(FreeCameraPlugin)
+        .init_resource::<WorldScale>()
+        .insert_resource(config)
+        .add_plugins(FreeCameraPlugin::default())
         // Example code plugins
-        .add_plugins((CameraPlugin, CameraSettingsPlugin, ScenePlugin))
+        .add_plugins((
+            CameraPlugin,
+            CameraSettingsPlugin,
+            PauseOverlayPlugin,
+            ExposurePlugin,
+            SpeedBlurPlugin,
+            GridSnapPlugin,
+            WaypointsPlugin,
+            AutoOrbitPlugin,
+            LandmarkPlugin,
+            FollowLightPlugin,
+            DollyZoomPlugin,
+            CameraRigPlugin,
+        ))
+        // Tooling plugins
+        .add_plugins((
+            PhotoModePlugin,
+            ConsolePlugin,
+            TelemetryPlugin,
+            TimelapsePlugin,
+            WindowGeometryPlugin,
+            WallPlacementPlugin,
+        ))
+        // Rendering plugins
+        .add_plugins((
+            StressTestPlugin,
+            OcclusionCullingPlugin,
+            LodPlugin,
+            AntiAliasingPlugin,
+            RenderScalePlugin,
+            GradientSkyPlugin,
+            MaterialLightingPlugin,
+            WallSurfacePlugin,
+            BackfaceCullingPlugin,
+            ShadowSettingsPlugin,
+            SoftShadowsPlugin,
+            LightingPresetsPlugin,
+            HeightFogPlugin,
+        ))
+        // Scene plugins
+        .add_plugins((
+            TriggerPlugin,
+            FootstepsPlugin,
+            AmbientSoundPlugin,
+            ColliderDebugPlugin,
+            BasisGizmoPlugin,
+            ExplorationMapPlugin,
+            ScenePlugin,
+        ))
         .add_plugins(
-            DefaultPlugins.set(ImagePlugin {
-                default_sampler: SamplerDescriptor {
-                    address_mode_u: AddressMode::Repeat,
-                    address_mode_v: AddressMode::Repeat,
-                    address_mode_w: AddressMode::Repeat,
-                    ..Default::default()
-                }
-                .into(),
-            }),
+            DefaultPlugins
+                .set(ImagePlugin {
+                    default_sampler: SamplerDescriptor {
+                        address_mode_u: AddressMode::Repeat,
+                        address_mode_v: AddressMode::Repeat,
+                        address_mode_w: AddressMode::Repeat,
+                        ..Default::default()
+                    }
+                    .into(),
+                })
+                .set(WindowPlugin {
+                    primary_window: Some(primary_window),
+                    ..default()
+                }),
         )
         .run();
 }
 
+// File the demo settings are kept in between runs.
+const CONFIG_PATH: &str = "config.ron";
+
+// Settings saved on exit and restored on the next launch.
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+struct Config {
+    #[serde(default)]
+    window: Option<WindowGeometry>,
+    #[serde(default)]
+    render_target: RenderTargetConfig,
+    #[serde(default)]
+    start_pose: StartPose,
+    #[serde(default)]
+    sensitivity_steps: SensitivitySteps,
+    // File the telemetry log is written to, `None` for `TELEMETRY_PATH`.
+    #[serde(default)]
+    telemetry_path: Option<PathBuf>,
+}
+
+// Where the camera starts out, in meters.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+struct StartPose {
+    position: Vec3,
+    look_direction: Vec3,
+}
+
+impl Default for StartPose {
+    fn default() -> Self {
+        Self {
+            position: Vec3::new(0.0, 1.0, 0.0),
+            look_direction: Vec3::X,
+        }
+    }
+}
+
+impl StartPose {
+    // Camera transform for a world with the given scale. A look direction that can't be
+    // normalized falls back to +X.
+    fn transform(&self, world_scale: f32) -> Transform {
+        let look_direction = Dir3::new(self.look_direction).unwrap_or_else(|error| {
+            warn!(
+                "Invalid start look direction {}: {error}, looking along +X instead",
+                self.look_direction
+            );
+            Dir3::X
+        });
+        Transform::from_translation(self.position * world_scale).looking_to(look_direction, Vec3::Y)
+    }
+}
+
+// How the Z/X keys change the mouse sensitivity each frame they are held.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+enum SensitivitySteps {
+    // Add or subtract a fixed amount, which is coarse at low sensitivities.
+    #[default]
+    Additive,
+    // Scale by a fixed factor, so every step is the same relative change.
+    Multiplicative,
+}
+
+// Change per frame for each kind of sensitivity step.
+const SENSITIVITY_STEP: f32 = 0.005;
+const SENSITIVITY_STEP_FACTOR: f32 = 1.025;
+
+impl SensitivitySteps {
+    // Sensitivity one step up or down from `sensitivity`, never going below the additive step.
+    fn step(self, sensitivity: f32, up: bool) -> f32 {
+        let sensitivity = match (self, up) {
+            (Self::Additive, true) => sensitivity + SENSITIVITY_STEP,
+            (Self::Additive, false) => sensitivity - SENSITIVITY_STEP,
+            (Self::Multiplicative, true) => sensitivity * SENSITIVITY_STEP_FACTOR,
+            (Self::Multiplicative, false) => sensitivity / SENSITIVITY_STEP_FACTOR,
+        };
+        sensitivity.max(SENSITIVITY_STEP)
+    }
+
+    fn help(self) -> String {
+        match self {
+            Self::Additive => format!("Z/X: decrease/increase sensitivity by {SENSITIVITY_STEP}\n"),
+            Self::Multiplicative => format!(
+                "Z/X: decrease/increase sensitivity by {:.1}%\n",
+                (SENSITIVITY_STEP_FACTOR - 1.0) * 100.0
+            ),
+        }
+    }
+}
+
+// What the free camera renders into.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+enum RenderTargetConfig {
+    // Straight into the window, which leaves the render scale at 1.
+    Window,
+    // Into an offscreen image shown on a full-screen UI node, sized to the window times the
+    // render scale. The image is available as the `SceneImage` resource for other uses, e.g.
+    // compositing it into a larger UI or recording it.
+    #[default]
+    Image,
+}
+
+impl Config {
+    fn save(&self, path: &Path) -> Result<(), BevyError> {
+        let ron = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, ron)?;
+        Ok(())
+    }
+
+    fn load(path: &Path) -> Result<Self, BevyError> {
+        let ron = std::fs::read_to_string(path)?;
+        Ok(ron::from_str(&ron)?)
+    }
+
+    // Falls back to the defaults when there is no config yet or it can't be read.
+    fn load_or_default(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+        Self::load(path).unwrap_or_else(|error| {
+            warn!("Could not load the config from {}: {error}", path.display());
+            Self::default()
+        })
+    }
+}
+
+// Number of world units per meter. The demo scene is modelled in meters, set this to e.g. 100.0
+// to match assets authored in centimeters. The scene geometry, the light and every distance
+// based camera setting are scaled by it. Gravity and jump speed scale linearly as well, so in
+// walk mode a jump reaches the same height relative to the scene and lasts just as long.
+#[derive(Resource)]
+struct WorldScale(f32);
+
+impl Default for WorldScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
 // Plugin that spawns the camera.
 struct CameraPlugin;
 impl Plugin for CameraPlugin {
@@ -37,28 +285,163 @@ impl Plugin for CameraPlugin {
     }
 }
 
-fn spawn_camera(mut commands: Commands) {
+fn spawn_camera(mut commands: Commands, world_scale: Res<WorldScale>, config: Res<Config>) {
+    let scale = world_scale.0;
+    let defaults = FreeCamera::default();
     commands.spawn((
         Camera3d::default(),
-        Transform::from_xyz(0.0, 1.0, 0.0).looking_to(Vec3::X, Vec3::Y),
+        Projection::Perspective(PerspectiveProjection {
+            near: 0.1 * scale,
+            far: 1000.0 * scale,
+            ..default()
+        }),
+        config.start_pose.transform(scale),
         // This component stores all camera settings and state, which is used by the FreeCameraPlugin to
         // control it. These properties can be changed at runtime, but beware the controller system is
         // constantly using and modifying those values unless the enabled field is false.
         FreeCamera {
             sensitivity: 0.2,
             friction: 25.0,
-            walk_speed: 3.0,
-            run_speed: 9.0,
-            ..default()
+            // Ease in and out of toggling the controller, so pausing looks smooth in captures
+            enable_transition: 0.3,
+            scroll_smoothing: 0.1,
+            walk_speed: 3.0 * scale,
+            run_speed: 9.0 * scale,
+            // The camera starts at eye height above the floor
+            stand_height: 1.0 * scale,
+            crouch_height: 0.6 * scale,
+            collision_radius: defaults.collision_radius * scale,
+            step_height: defaults.step_height * scale,
+            gravity: defaults.gravity * scale,
+            jump_speed: defaults.jump_speed * scale,
+            terminal_velocity: defaults.terminal_velocity * scale,
+            proximity_reference_distance: defaults.proximity_reference_distance * scale,
+            // Well below the floor, so only falling through it triggers a recovery
+            kill_plane: Some(-10.0 * scale),
+            ..defaults
         },
+        // Keeps the orbiting control schemes from swinging the camera through walls
+        SpringArm::new(SpringArm::default().min_length * scale),
     ));
 }
 
+// Plugin that adds fixed shots of the scene next to the free camera. The numpad keys without a
+// nudge binding switch which camera renders, the free camera only moves while it is the one.
+struct CameraRigPlugin;
+impl Plugin for CameraRigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraRig>()
+            .add_systems(Startup, spawn_rig_cameras.after(spawn_scene_view))
+            .add_systems(PostStartup, spawn_camera_rig_text.after(spawn_text))
+            .add_systems(Update, (switch_rig_camera, update_camera_rig_text).chain());
+    }
+}
+
+// Keys selecting the rig cameras, in order.
+const CAMERA_RIG_KEYS: [KeyCode; 6] = [
+    KeyCode::Numpad0,
+    KeyCode::Numpad1,
+    KeyCode::Numpad3,
+    KeyCode::Numpad5,
+    KeyCode::Numpad7,
+    KeyCode::Numpad9,
+];
+
+// The cameras that can be switched between, and which one of them renders.
+#[derive(Resource, Default)]
+struct CameraRig {
+    cameras: Vec<(String, Entity)>,
+    active: usize,
+}
+
+#[derive(Component)]
+struct CameraRigText;
+
+fn spawn_rig_cameras(
+    mut commands: Commands,
+    mut rig: ResMut<CameraRig>,
+    camera: Single<(Entity, &Projection, Option<&RenderTarget>), With<FreeCamera>>,
+    world_scale: Res<WorldScale>,
+) {
+    let (free_camera, projection, target) = *camera;
+    rig.cameras.push(("free".to_string(), free_camera));
+
+    // Shots of the demo world, in meters
+    let shots = [
+        (
+            "overview",
+            Transform::from_xyz(10.0, 40.0, 17.0).looking_at(Vec3::new(10.0, 0.0, 17.5), Vec3::X),
+        ),
+        (
+            "doorway",
+            Transform::from_xyz(15.0, 1.7, 13.0).looking_at(Vec3::new(10.0, 1.5, 23.0), Vec3::Y),
+        ),
+    ];
+    for (name, transform) in shots {
+        // Same lens and target as the free camera, so switching only changes the viewpoint
+        let mut entity = commands.spawn((
+            Camera3d::default(),
+            Camera {
+                is_active: false,
+                ..default()
+            },
+            projection.clone(),
+            Transform {
+                translation: transform.translation * world_scale.0,
+                ..transform
+            },
+        ));
+        if let Some(target) = target {
+            entity.insert(target.clone());
+        }
+        rig.cameras.push((name.to_string(), entity.id()));
+    }
+}
+
+fn spawn_camera_rig_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((CameraRigText, Text::new("")));
+}
+
+fn switch_rig_camera(
+    mut rig: ResMut<CameraRig>,
+    mut camera_query: Query<&mut Camera>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    let Some(index) = CAMERA_RIG_KEYS
+        .iter()
+        .position(|key| input.just_pressed(*key))
+        .filter(|index| *index < rig.cameras.len())
+    else {
+        return;
+    };
+
+    rig.active = index;
+    for (camera_index, (_, entity)) in rig.cameras.iter().enumerate() {
+        if let Ok(mut camera) = camera_query.get_mut(*entity) {
+            camera.is_active = camera_index == index;
+        }
+    }
+}
+
+fn update_camera_rig_text(mut text: Single<&mut Text, With<CameraRigText>>, rig: Res<CameraRig>) {
+    if !rig.is_changed() {
+        return;
+    }
+    if let Some((name, _)) = rig.cameras.get(rig.active) {
+        text.0 = format!("Camera: {name} ({}/{})", rig.active + 1, rig.cameras.len());
+    }
+}
+
 // Plugin that handles camera settings controls and information text
 struct CameraSettingsPlugin;
 impl Plugin for CameraSettingsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostStartup, spawn_text)
+        app.add_plugins(EntityCountDiagnosticsPlugin::default())
+            .init_resource::<AdvancedStats>()
+            .init_resource::<PerformanceHud>()
+            .add_systems(PostStartup, spawn_text)
             .add_systems(Update, (update_camera_settings, update_text));
     }
 }
@@ -66,7 +449,23 @@ impl Plugin for CameraSettingsPlugin {
 #[derive(Component)]
 struct InfoText;
 
-fn spawn_text(mut commands: Commands, free_camera_query: Query<&FreeCamera>) {
+// Whether the settings readout includes the extra lines for tuning, toggled with F4.
+#[derive(Resource, Default)]
+struct AdvancedStats {
+    enabled: bool,
+}
+
+// Whether the settings readout includes the entity and draw call counts, toggled with Shift+F4.
+#[derive(Resource, Default)]
+struct PerformanceHud {
+    enabled: bool,
+}
+
+// Column holding the settings readout. Other plugins append their own text lines to it.
+#[derive(Component)]
+struct InfoPanel;
+
+fn spawn_text(mut commands: Commands, input_map: Res<InputMap<Action>>, config: Res<Config>) {
     commands.spawn((
         Node {
             position_type: PositionType::Absolute,
@@ -74,10 +473,7 @@ fn spawn_text(mut commands: Commands, free_camera_query: Query<&FreeCamera>) {
             left: px(12),
             ..default()
         },
-        children![Text::new(format!(
-            "{}",
-            free_camera_query.single().unwrap()
-        ))],
+        children![Text::new(format!("{}", *input_map))],
     ));
     commands.spawn((
         Node {
@@ -86,37 +482,90 @@ fn spawn_text(mut commands: Commands, free_camera_query: Query<&FreeCamera>) {
             left: px(12),
             ..default()
         },
-        children![Text::new(concat![
-            "Z/X: decrease/increase sensitivity\n",
+        children![Text::new(config.sensitivity_steps.help() + concat![
             "C/V: decrease/increase friction\n",
-            "F/G: decrease/increase scroll factor\n",
-            "B: enable/disable controller",
+            "Delete: enable/disable camera inertia\n",
+            "F/G: decrease/increase speed, like scrolling\n",
+            "B: enable/disable controller\n",
+            "K: switch between flying and walking\n",
+            "Y: cycle FPS, orbit and CAD controls\n",
+            "9: level out the camera\n",
+            "0: snap the camera to a half meter grid\n",
+            ";/': add/go to next waypoint\n",
+            "\\: look at the next landmark\n",
+            "Tab: start/stop a dolly zoom on what the camera looks at\n",
+            "Numpad 0/1/3: view through the free, overview, doorway camera\n",
+            "T: enable/disable auto exposure\n",
+            "[/]: decrease/increase exposure adaptation speed\n",
+            "N: enable/disable motion blur\n",
+            "P: enter/exit photo mode\n",
+            "`: open/close console\n",
+            "L: start/stop logging camera telemetry\n",
+            "H/J: spawn/despawn stress test cubes\n",
+            "O: enable/disable occlusion culling\n",
+            "I: cycle anti-aliasing method\n",
+            "-/=: decrease/increase render scale\n",
+            "R: switch between gradient sky and skybox texture\n",
+            "1/2, 3/4: shift zenith, horizon sky hue\n",
+            "U: cycle authored, lit and unlit scene materials\n",
+            "5/6, 7/8: decrease/increase wall metallic, roughness\n",
+            "F1: show/hide collider bounds, with Shift the camera axes\n",
+            "F2: enable/disable proximity speed scaling\n",
+            "F3: invert/restore scroll direction\n",
+            "F4: show/hide advanced stats, with Shift the entity and draw call counts\n",
+            "F5: switch between mouse and trackpad sensitivity\n",
+            "F6/F7: save/load waypoints\n",
+            "F8: turn the over-the-shoulder fill light on/off, with Shift cycle lighting presets\n",
+            "F9: enable/disable backface culling\n",
+            "F10: start/stop timelapse capture, with Shift at a fixed exposure\n",
+            "/: cycle shadow map resolution\n",
+            "F11/F12: cycle sun shadow cascade count, distance\n",
+            "Insert: enable/disable soft shadows\n",
+            "Home/End: decrease/increase shadow softness\n",
+            "Shift+Insert, Shift+Home/End: enable/disable height fog, decrease/increase density\n",
+            "Enter: start/stop placing walls, Backspace: turn the wall, Ctrl+Z: undo\n",
+            "Shift+Enter: export the placed objects to the scene file\n",
+            "Shift+F2/F3: show/hide the exploration map, forget explored areas",
         ]),],
     ));
 
     // Mutable text marked with component
     commands.spawn((
+        InfoPanel,
         Node {
             position_type: PositionType::Absolute,
             top: px(12),
             right: px(12),
+            flex_direction: FlexDirection::Column,
             ..default()
         },
         children![(InfoText, Text::new(""))],
     ));
 }
 
+// Scroll wheel notches per second the F/G speed keys are worth while held.
+const SPEED_KEY_STEPS_PER_SECOND: f32 = 4.0;
+
 fn update_camera_settings(
     mut camera_query: Query<(&mut FreeCamera, &mut FreeCameraState)>,
+    mut advanced_stats: ResMut<AdvancedStats>,
+    mut performance_hud: ResMut<PerformanceHud>,
     input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    config: Res<Config>,
 ) {
     let (mut free_camera, mut free_camera_state) = camera_query.single_mut().unwrap();
 
-    if input.pressed(KeyCode::KeyZ) {
-        free_camera.sensitivity = (free_camera.sensitivity - 0.005).max(0.005);
+    // Ctrl+Z undoes a wall placement instead
+    if input.pressed(KeyCode::KeyZ)
+        && !input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+    {
+        free_camera.sensitivity = config
+            .sensitivity_steps
+            .step(free_camera.sensitivity, false);
     }
     if input.pressed(KeyCode::KeyX) {
-        free_camera.sensitivity += 0.005;
+        free_camera.sensitivity = config.sensitivity_steps.step(free_camera.sensitivity, true);
     }
     if input.pressed(KeyCode::KeyC) {
         free_camera.friction = (free_camera.friction - 0.2).max(0.0);
@@ -124,343 +573,4212 @@ fn update_camera_settings(
     if input.pressed(KeyCode::KeyV) {
         free_camera.friction += 0.2;
     }
+    if input.just_pressed(KeyCode::Delete) {
+        free_camera.inertia = !free_camera.inertia;
+    }
+    // Same speed multiplier as the scroll wheel, at a steady number of notches per second
+    let speed_steps = SPEED_KEY_STEPS_PER_SECOND * time.delta_secs();
     if input.pressed(KeyCode::KeyF) {
-        free_camera.scroll_factor = (free_camera.scroll_factor - 0.02).max(0.02);
+        free_camera_state.adjust_speed(&free_camera, -speed_steps);
     }
     if input.pressed(KeyCode::KeyG) {
-        free_camera.scroll_factor += 0.02;
+        free_camera_state.adjust_speed(&free_camera, speed_steps);
     }
     if input.just_pressed(KeyCode::KeyB) {
         free_camera_state.enabled = !free_camera_state.enabled;
     }
+    if input.just_pressed(KeyCode::KeyK) {
+        free_camera.movement_mode = match free_camera.movement_mode {
+            MovementMode::Fly => MovementMode::Walk,
+            MovementMode::Walk => MovementMode::Fly,
+        };
+    }
+    if input.just_pressed(KeyCode::Digit9) {
+        free_camera_state.level_out();
+    }
+    let shift = input.pressed(KeyCode::ShiftLeft);
+    if input.just_pressed(KeyCode::F2) && !shift {
+        free_camera.proximity_speed_scaling = !free_camera.proximity_speed_scaling;
+    }
+    if input.just_pressed(KeyCode::F3) && !shift {
+        free_camera.invert_scroll = !free_camera.invert_scroll;
+    }
+    if input.just_pressed(KeyCode::F5) {
+        free_camera.trackpad_mode = !free_camera.trackpad_mode;
+    }
+    if input.just_pressed(KeyCode::F4) {
+        if shift {
+            performance_hud.enabled = !performance_hud.enabled;
+        } else {
+            advanced_stats.enabled = !advanced_stats.enabled;
+        }
+    }
+    if input.just_pressed(KeyCode::KeyY) {
+        free_camera.control_scheme = match free_camera.control_scheme {
+            ControlScheme::Fps => ControlScheme::Orbit,
+            ControlScheme::Orbit => ControlScheme::Cad,
+            ControlScheme::Cad => ControlScheme::Fps,
+        };
+    }
 }
 
 fn update_text(
     mut text_query: Query<&mut Text, With<InfoText>>,
-    camera_query: Query<(&FreeCamera, &FreeCameraState)>,
+    camera_query: Query<(&Transform, &FreeCamera, &FreeCameraState, &Projection)>,
+    advanced_stats: Res<AdvancedStats>,
+    performance_hud: Res<PerformanceHud>,
+    diagnostics: Res<DiagnosticsStore>,
 ) {
     let mut text = text_query.single_mut().unwrap();
 
-    let (free_camera, free_camera_state) = camera_query.single().unwrap();
+    let (transform, free_camera, free_camera_state, projection) = camera_query.single().unwrap();
 
-    text.0 = format!(
-        "Enabled: {},\nSensitivity: {:.03}\nFriction: {:.01}\nScroll factor: {:.02}\nWalk Speed: {:.02}\nRun Speed: {:.02}\nSpeed: {:.02}",
-        free_camera_state.enabled,
-        free_camera.sensitivity,
-        free_camera.friction,
-        free_camera.scroll_factor,
-        free_camera.walk_speed,
-        free_camera.run_speed,
-        free_camera_state.velocity.length(),
-    );
+    let mut lines = vec![
+        format!("Enabled: {},", free_camera_state.enabled),
+        free_camera.fmt_controls(),
+        free_camera.fmt_look(),
+        free_camera.fmt_friction(),
+        free_camera.fmt_scroll(),
+        free_camera.fmt_speeds(free_camera_state),
+        free_camera_state.fmt_velocity(),
+        free_camera.fmt_nudge(),
+        free_camera.fmt_proximity_speed(free_camera_state),
+        free_camera_state.fmt_last_scroll(),
+    ];
+    if advanced_stats.enabled {
+        lines.push(match projection {
+            Projection::Perspective(perspective) => {
+                format!("FOV: {:.01}°", perspective.fov.to_degrees())
+            }
+            Projection::Orthographic(orthographic) => {
+                format!("Ortho scale: {:.02}", orthographic.scale)
+            }
+            Projection::Custom(_) => "Projection: custom".to_string(),
+        });
+        let input = free_camera_state.input_direction();
+        lines.push(format!(
+            "Input direction: {:.02} {:.02} {:.02}",
+            input.x, input.y, input.z
+        ));
+        if free_camera.movement_mode == MovementMode::Walk {
+            let feet = free_camera_state.feet_position(free_camera, transform.translation);
+            lines.push(format!(
+                "Feet: {:.02} {:.02} {:.02}, eye height {:.02}",
+                feet.x, feet.y, feet.z, free_camera_state.eye_height
+            ));
+        }
+    }
+    if performance_hud.enabled {
+        // Smoothed, so the counts don't flicker while walls move in and out of view. The mesh
+        // counts are measured by the stress test plugin.
+        let value = |path| {
+            diagnostics
+                .get(path)
+                .and_then(|diagnostic| diagnostic.smoothed())
+                .unwrap_or_default()
+        };
+        lines.push(format!(
+            "Entities: {:.0}, visible meshes: {:.0}, draw calls: ~{:.0}",
+            value(&EntityCountDiagnosticsPlugin::ENTITY_COUNT),
+            value(&VISIBLE_MESHES),
+            value(&MESH_BATCHES),
+        ));
+    }
+    text.0 = lines.join("\n");
 }
 
-// Plugin that spawns the scene and lighting.
-struct ScenePlugin;
-impl Plugin for ScenePlugin {
+// Plugin that dims the scene while the camera controller is disabled
+struct PauseOverlayPlugin;
+impl Plugin for PauseOverlayPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (spawn_lights, spawn_world));
+        app.add_systems(Startup, spawn_pause_overlay)
+            .add_systems(Update, fade_pause_overlay);
+    }
+}
+
+// Full-screen dim layer shown while the controller is paused with B.
+#[derive(Component)]
+struct PauseOverlay {
+    // Opacity of the overlay once fully faded in, from 0.0 to 1.0.
+    dim: f32,
+    // How much opacity is gained or lost per second while fading.
+    fade_speed: f32,
+}
+
+impl Default for PauseOverlay {
+    fn default() -> Self {
+        Self {
+            dim: 0.5,
+            fade_speed: 3.0,
+        }
     }
 }
 
-fn spawn_lights(mut commands: Commands) {
-    // Main light
+fn spawn_pause_overlay(mut commands: Commands) {
     commands.spawn((
-        PointLight {
-            color: Color::from(tailwind::NEUTRAL_300),
-            shadows_enabled: true,
+        PauseOverlay::default(),
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
             ..default()
         },
-        Transform::from_xyz(0.0, 45.0, 0.0),
+        BackgroundColor(Color::BLACK.with_alpha(0.0)),
+        // Keep the overlay underneath every other UI node and let clicks fall through it, so
+        // any settings UI stays usable while paused.
+        GlobalZIndex(-1),
+        FocusPolicy::Pass,
+        Pickable::IGNORE,
     ));
 }
 
-fn spawn_world(
+fn fade_pause_overlay(
+    time: Res<Time<Real>>,
+    camera_query: Query<&FreeCameraState>,
+    mut overlay_query: Query<(&PauseOverlay, &mut BackgroundColor)>,
+) {
+    let Ok(free_camera_state) = camera_query.single() else {
+        return;
+    };
+
+    for (overlay, mut background) in &mut overlay_query {
+        let target = if free_camera_state.enabled {
+            0.0
+        } else {
+            overlay.dim
+        };
+        let alpha = background.0.alpha();
+        let step = overlay.fade_speed * time.delta_secs();
+        let alpha = if alpha < target {
+            (alpha + step).min(target)
+        } else {
+            (alpha - step).max(target)
+        };
+        if alpha != background.0.alpha() {
+            background.0.set_alpha(alpha);
+        }
+    }
+}
+
+// Plugin that adds an optional auto exposure (eye adaptation) effect to the camera
+struct ExposurePlugin;
+impl Plugin for ExposurePlugin {
+    fn build(&self, app: &mut App) {
+        // Auto exposure is not part of the default plugins, it needs its own histogram compute pass.
+        app.add_plugins(AutoExposurePlugin)
+            .init_resource::<EyeAdaptation>()
+            .add_systems(PostStartup, spawn_exposure_text.after(spawn_text))
+            .add_systems(Update, update_exposure_text)
+            // Late, so screenshots requested during Update already hold the exposure this frame
+            .add_systems(PostUpdate, update_eye_adaptation);
+    }
+}
+
+// Auto exposure settings, applied to the camera whenever they change.
+#[derive(Resource)]
+struct EyeAdaptation {
+    enabled: bool,
+    // Multiplier on the default brighten/darken adaptation speeds.
+    speed: f32,
+}
+
+impl Default for EyeAdaptation {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            speed: 1.0,
+        }
+    }
+}
+
+// Marks a screenshot that should be captured at a fixed exposure. Adaptation is paused while any
+// of these are waiting to be captured.
+#[derive(Component, Default)]
+struct FixedExposureCapture {
+    // Frames spent waiting so far.
+    frames: u32,
+}
+
+// Frames after which a fixed exposure capture is given up on, in case the screenshot failed and
+// never finishes. Adaptation resumes either way.
+const FIXED_EXPOSURE_MAX_FRAMES: u32 = 30;
+
+#[derive(Component)]
+struct ExposureText;
+
+fn spawn_exposure_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((ExposureText, Text::new("")));
+}
+
+fn update_eye_adaptation(
     mut commands: Commands,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    asset_server: Res<AssetServer>,
+    mut eye_adaptation: ResMut<EyeAdaptation>,
+    camera_query: Query<Entity, With<FreeCamera>>,
+    mut capture_query: Query<(Entity, &mut FixedExposureCapture)>,
+    mut paused: Local<bool>,
+    input: Res<ButtonInput<KeyCode>>,
 ) {
-    let cube = meshes.add(Cuboid::new(1.0, 1.0, 1.0));
-    let floor = meshes.add(Plane3d::new(
-        Vec3::new(0.0, 100.0, 0.0),
-        Vec2::new(20.0, 35.0),
-    ));
-    let sphere = meshes.add(Sphere::new(0.5));
-
-    let wall = meshes.add(Cuboid::new(0.2, 4.0, 3.0));
-    let back_wall = meshes.add(Cuboid::new(50.0, 5.0, 0.35));
-    let cub_wall = meshes.add(Cuboid::new(5.0, 5.0, 0.2));
-    let tav_wall = meshes.add(Cuboid::new(9.0, 5.0, 0.35));
-
-    let long_wall = meshes.add(Cuboid::new(80.0, 5.0, 0.35));
-    let cub_ent = meshes.add(Cuboid::new(2.0, 5.0, 0.15));
-    let shor_ent = meshes.add(Cuboid::new(1.0, 5.0, 0.15));
-
-    let hall_1 = meshes.add(Cuboid::new(5.0, 5.0, 0.15));
-
-    let column = meshes.add(Cylinder::new(0.3, 5.0));
-    let blue_material = materials.add(Color::from(tailwind::BLUE_700));
-    let red_material = materials.add(Color::from(tailwind::RED_950));
-    let white_material = materials.add(Color::WHITE);
-    let texture_handle = asset_server.load("textures/marble.png");
-    let skyeee = asset_server.load("textures/skybox.png");
-    let floa = asset_server.load("textures/floor.png");
-
-    let material_handle = materials.add(StandardMaterial {
-        base_color_texture: Some(texture_handle.clone()),
-        alpha_mode: AlphaMode::Blend,
-        unlit: true,
-        ..default()
-    });
-    let flooo = materials.add(StandardMaterial {
-        base_color_texture: Some(floa.clone()),
-        alpha_mode: AlphaMode::Blend,
-        unlit: true,
-        ..default()
-    });
-    let skybox = materials.add(StandardMaterial {
-        base_color_texture: Some(skyeee.clone()),
-        alpha_mode: AlphaMode::Blend,
-        unlit: true,
-        ..default()
-    });
-    let sky = meshes.add(Circle::new(100.0));
-    // Top side of floor
+    if input.just_pressed(KeyCode::KeyT) {
+        eye_adaptation.enabled = !eye_adaptation.enabled;
+    }
+    if input.pressed(KeyCode::BracketLeft) {
+        eye_adaptation.speed = (eye_adaptation.speed - 0.02).max(0.02);
+    }
+    if input.pressed(KeyCode::BracketRight) {
+        eye_adaptation.speed += 0.02;
+    }
 
-    commands.spawn((
-        Mesh3d(floor.clone()),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color_texture: Some(asset_server.load_with_settings(
-                "textures/floor.png",
-                |s: &mut _| {
-                    *s = ImageLoaderSettings {
-                        sampler: ImageSampler::Descriptor(ImageSamplerDescriptor {
-                            // rewriting mode to repeat image,
-                            address_mode_u: ImageAddressMode::MirrorRepeat,
-                            address_mode_v: ImageAddressMode::MirrorRepeat,
-
-                            ..default()
-                        }),
+    // Captured screenshots are despawned, failed ones time out
+    let mut capturing = false;
+    for (entity, mut capture) in &mut capture_query {
+        capture.frames += 1;
+        if capture.frames > FIXED_EXPOSURE_MAX_FRAMES {
+            warn!("Gave up waiting for a fixed exposure screenshot, resuming adaptation");
+            commands.entity(entity).remove::<FixedExposureCapture>();
+        } else {
+            capturing = true;
+        }
+    }
+    let pause_changed = *paused != capturing;
+    *paused = capturing;
 
-                        ..default()
-                    }
-                },
-            )),
-            emissive: LinearRgba::rgb(0.244, 0.166, 0.172),
-            // uv_transform used here for proportions only, but it is full Affine2
-            // that's why you can use rotation and shift also
-            uv_transform: Affine2::from_scale(Vec2::new(20., 20.)),
-            ..default()
-        })),
-    ));
+    if !eye_adaptation.is_changed() && !pause_changed {
+        return;
+    }
 
-    // Tall wall
-    commands.spawn((
-        Mesh3d(wall.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform::from_xyz(-3.0, 2.0, 0.0),
-    ));
-    commands.spawn((
-        Mesh3d(long_wall.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform {
-            translation: Vec3::new(20.0, 0.0, 0.0),
-            rotation: Quat::from_euler(EulerRot::YXZEx, FRAC_PI_2, 0.0, 0.0),
-            ..default()
-        },
-    ));
-    commands.spawn((
-        Mesh3d(long_wall.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform {
-            translation: Vec3::new(0.0, 0.0, 0.0),
-            rotation: Quat::from_euler(EulerRot::YXZEx, FRAC_PI_2, 0.0, 0.0),
-            ..default()
-        },
-    ));
-    commands.spawn((
-        Mesh3d(back_wall.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform::from_xyz(0.0, 0.0, 35.0),
-    ));
+    // Zero speeds hold the exposure where adaptation left it
+    let speed = if capturing { 0.0 } else { eye_adaptation.speed };
+    for camera in &camera_query {
+        if eye_adaptation.enabled {
+            let defaults = AutoExposure::default();
+            commands.entity(camera).insert(AutoExposure {
+                speed_brighten: defaults.speed_brighten * speed,
+                speed_darken: defaults.speed_darken * speed,
+                ..defaults
+            });
+        } else {
+            commands.entity(camera).remove::<AutoExposure>();
+        }
+    }
+}
 
-    commands.spawn((
-        Mesh3d(cub_wall.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform::from_xyz(18.0, 0.0, 27.0),
-    ));
-    commands.spawn((
-        Mesh3d(cub_wall.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform::from_xyz(18.0, 0.0, 23.0),
-    ));
-    commands.spawn((
-        Mesh3d(cub_wall.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform::from_xyz(18.0, 0.0, 20.0),
-    ));
-    commands.spawn((
-        Mesh3d(cub_wall.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform::from_xyz(18.0, 0.0, 16.0),
-    ));
+fn update_exposure_text(
+    mut text_query: Query<&mut Text, With<ExposureText>>,
+    camera_query: Query<Option<&Exposure>, With<FreeCamera>>,
+    eye_adaptation: Res<EyeAdaptation>,
+) {
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+    let Ok(exposure) = camera_query.single() else {
+        return;
+    };
 
-    commands.spawn((
-        Mesh3d(cub_wall.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform::from_xyz(18.0, 0.0, 14.0),
-    ));
-    commands.spawn((
-        Mesh3d(cub_wall.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform::from_xyz(18.0, 0.0, 9.0),
-    ));
-    commands.spawn((
-        Mesh3d(cub_wall.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform::from_xyz(18.0, 0.0, 5.0),
-    ));
+    text.0 = format!(
+        "Exposure: EV100 {:.01}\nAuto exposure: {} (speed {:.02})",
+        exposure.copied().unwrap_or_default().ev100,
+        eye_adaptation.enabled,
+        eye_adaptation.speed,
+    );
+}
 
-    commands.spawn((
-        Mesh3d(cub_ent.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform {
-            translation: Vec3::new(15.5, 0.0, 26.0),
-            rotation: Quat::from_euler(EulerRot::YXZEx, FRAC_PI_2, 0.0, 0.0),
-            ..default()
-        },
-    ));
-    commands.spawn((
-        Mesh3d(cub_ent.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform {
-            translation: Vec3::new(15.5, 0.0, 23.0),
-            rotation: Quat::from_euler(EulerRot::YXZEx, FRAC_PI_2, 0.0, 0.0),
-            ..default()
-        },
-    ));
-    commands.spawn((
-        Mesh3d(cub_ent.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform {
-            translation: Vec3::new(15.5, 0.0, 20.0),
-            rotation: Quat::from_euler(EulerRot::YXZEx, FRAC_PI_2, 0.0, 0.0),
-            ..default()
-        },
-    ));
+// Plugin that adds motion blur whose strength follows the camera speed
+struct SpeedBlurPlugin;
+impl Plugin for SpeedBlurPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpeedBlur>()
+            .add_systems(PostStartup, spawn_speed_blur_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (toggle_speed_blur, update_speed_blur, update_speed_blur_text).chain(),
+            );
+    }
+}
 
-    commands.spawn((
-        Mesh3d(hall_1.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform {
-            translation: Vec3::new(15.5, 0.0, 11.5),
-            rotation: Quat::from_euler(EulerRot::YXZEx, FRAC_PI_2, 0.0, 0.0),
-            ..default()
-        },
-    ));
-    commands.spawn((
-        Mesh3d(shor_ent.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform {
-            translation: Vec3::new(15.5, 0.0, 13.0),
-            rotation: Quat::from_euler(EulerRot::YXZEx, FRAC_PI_2, 0.0, 0.0),
-            ..default()
-        },
-    ));
+#[derive(Resource)]
+struct SpeedBlur {
+    enabled: bool,
+    // Shutter angle used at or above `full_blur_speed`.
+    max_shutter_angle: f32,
+    // Camera speed at which the blur reaches its maximum.
+    full_blur_speed: f32,
+}
 
-    commands.spawn((
-        Mesh3d(tav_wall.clone()),
-        MeshMaterial3d(white_material.clone()),
-        Transform::from_xyz(16.0, 0.0, 0.0),
-    ));
+impl Default for SpeedBlur {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_shutter_angle: 1.0,
+            full_blur_speed: 15.0,
+        }
+    }
+}
 
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(13.0, 0.0, 25.0),
-    ));
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(13.0, 0.0, 24.0),
-    ));
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(13.0, 0.0, 23.0),
-    ));
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(13.0, 0.0, 22.0),
-    ));
+impl SpeedBlur {
+    // Shutter angle for the given speed, zero when standing still so screenshots stay crisp.
+    fn shutter_angle(&self, speed: f32) -> f32 {
+        if self.full_blur_speed <= 0.0 {
+            return 0.0;
+        }
+        (speed / self.full_blur_speed).clamp(0.0, 1.0) * self.max_shutter_angle
+    }
+}
 
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(12.0, 0.0, 25.0),
-    ));
+#[derive(Component)]
+struct SpeedBlurText;
 
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(11.0, 0.0, 25.0),
-    ));
+fn spawn_speed_blur_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((SpeedBlurText, Text::new("")));
+}
 
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(10.0, 0.0, 25.0),
-    ));
+fn toggle_speed_blur(
+    mut commands: Commands,
+    mut speed_blur: ResMut<SpeedBlur>,
+    camera_query: Query<Entity, With<FreeCamera>>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    if !input.just_pressed(KeyCode::KeyN) {
+        return;
+    }
+    speed_blur.enabled = !speed_blur.enabled;
 
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(9.0, 0.0, 25.0),
-    ));
+    for camera in &camera_query {
+        if speed_blur.enabled {
+            commands.entity(camera).insert(MotionBlur {
+                shutter_angle: 0.0,
+                samples: 4,
+            });
+        } else {
+            commands.entity(camera).remove::<MotionBlur>();
+        }
+    }
+}
 
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(9.0, 0.0, 24.0),
-    ));
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(9.0, 0.0, 23.0),
-    ));
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(9.0, 0.0, 22.0),
-    ));
+fn update_speed_blur(
+    speed_blur: Res<SpeedBlur>,
+    mut camera_query: Query<(&FreeCameraState, &mut MotionBlur)>,
+) {
+    for (free_camera_state, mut motion_blur) in &mut camera_query {
+        motion_blur.shutter_angle = speed_blur.shutter_angle(free_camera_state.velocity.length());
+    }
+}
 
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(12.0, 0.0, 24.0),
-    ));
+fn update_speed_blur_text(
+    mut text_query: Query<&mut Text, With<SpeedBlurText>>,
+    camera_query: Query<Option<&MotionBlur>, With<FreeCamera>>,
+) {
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+    let Ok(motion_blur) = camera_query.single() else {
+        return;
+    };
 
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(11.0, 0.0, 23.0),
-    ));
+    text.0 = match motion_blur {
+        Some(motion_blur) => format!("Motion blur: {:.02}", motion_blur.shutter_angle),
+        None => "Motion blur: off".to_string(),
+    };
+}
 
-    commands.spawn((
-        Mesh3d(column.clone()),
-        MeshMaterial3d(material_handle.clone()),
-        Transform::from_xyz(10.0, 0.0, 22.0),
-    ));
+// Plugin for a photo mode that freezes time, hides the UI and frees the cursor. The cursor stays
+// free until photo mode ends, the grab button doesn't grab it and the mouse doesn't look around.
+struct PhotoModePlugin;
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<PhotoMode>()
+            .init_resource::<PhotoModeSettings>()
+            .add_systems(Update, toggle_photo_mode)
+            .add_systems(OnEnter(PhotoMode::On), enter_photo_mode)
+            .add_systems(OnExit(PhotoMode::On), exit_photo_mode);
+    }
+}
 
-    commands.spawn((
-        Mesh3d(sky.clone()),
-        MeshMaterial3d(skybox.clone()),
-        Transform {
-            translation: Vec3::new(0.0, 65.0, 0.0),
-            rotation: Quat::from_euler(EulerRot::YXZEx, 0.0, FRAC_PI_2, 0.0),
+#[derive(States, Default, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum PhotoMode {
+    #[default]
+    Off,
+    On,
+}
+
+#[derive(Resource)]
+struct PhotoModeSettings {
+    // Multiplier applied to the camera walk and run speeds while framing a shot.
+    movement_scale: f32,
+}
+
+impl Default for PhotoModeSettings {
+    fn default() -> Self {
+        Self {
+            movement_scale: 0.25,
+        }
+    }
+}
+
+// UI nodes at the top of their hierarchy, hiding these hides the whole UI. The scene itself is
+// drawn through a UI node as well, which has to stay visible.
+type UiRoots = (With<Node>, Without<ChildOf>, Without<SceneView>);
+
+// Everything photo mode changes, captured on enter so exit can put it back exactly.
+#[derive(Resource)]
+struct PhotoModeSnapshot {
+    time_paused: bool,
+    ui_visibility: Vec<(Entity, Visibility)>,
+    cursor_options: Vec<(Entity, CursorOptions)>,
+    walk_speed: f32,
+    run_speed: f32,
+    cursor_grab_enabled: bool,
+}
+
+fn toggle_photo_mode(
+    input: Res<ButtonInput<KeyCode>>,
+    photo_mode: Res<State<PhotoMode>>,
+    mut next_photo_mode: ResMut<NextState<PhotoMode>>,
+) {
+    if input.just_pressed(KeyCode::KeyP) {
+        next_photo_mode.set(match photo_mode.get() {
+            PhotoMode::Off => PhotoMode::On,
+            PhotoMode::On => PhotoMode::Off,
+        });
+    }
+}
+
+fn enter_photo_mode(
+    mut commands: Commands,
+    settings: Res<PhotoModeSettings>,
+    mut time: ResMut<Time<Virtual>>,
+    mut ui_query: Query<(Entity, &mut Visibility), UiRoots>,
+    mut cursor_query: Query<(Entity, &mut CursorOptions)>,
+    mut camera_query: Query<&mut FreeCamera>,
+) {
+    let Ok(mut free_camera) = camera_query.single_mut() else {
+        return;
+    };
+
+    commands.insert_resource(PhotoModeSnapshot {
+        time_paused: time.is_paused(),
+        ui_visibility: ui_query
+            .iter()
+            .map(|(entity, visibility)| (entity, *visibility))
+            .collect(),
+        cursor_options: cursor_query
+            .iter()
+            .map(|(entity, cursor_options)| (entity, cursor_options.clone()))
+            .collect(),
+        walk_speed: free_camera.walk_speed,
+        run_speed: free_camera.run_speed,
+        cursor_grab_enabled: free_camera.cursor_grab_enabled,
+    });
+
+    time.pause();
+    for (_, mut visibility) in &mut ui_query {
+        *visibility = Visibility::Hidden;
+    }
+    for (_, mut cursor_options) in &mut cursor_query {
+        cursor_options.grab_mode = CursorGrabMode::None;
+        cursor_options.visible = true;
+    }
+    free_camera.walk_speed *= settings.movement_scale;
+    free_camera.run_speed *= settings.movement_scale;
+    free_camera.cursor_grab_enabled = false;
+}
+
+fn exit_photo_mode(
+    mut commands: Commands,
+    snapshot: Option<Res<PhotoModeSnapshot>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut ui_query: Query<&mut Visibility, With<Node>>,
+    mut cursor_query: Query<&mut CursorOptions>,
+    mut camera_query: Query<&mut FreeCamera>,
+) {
+    let Some(snapshot) = snapshot else {
+        return;
+    };
+
+    if !snapshot.time_paused {
+        time.unpause();
+    }
+    for (entity, saved) in &snapshot.ui_visibility {
+        if let Ok(mut visibility) = ui_query.get_mut(*entity) {
+            *visibility = *saved;
+        }
+    }
+    for (entity, saved) in &snapshot.cursor_options {
+        if let Ok(mut cursor_options) = cursor_query.get_mut(*entity) {
+            *cursor_options = saved.clone();
+        }
+    }
+    if let Ok(mut free_camera) = camera_query.single_mut() {
+        free_camera.walk_speed = snapshot.walk_speed;
+        free_camera.run_speed = snapshot.run_speed;
+        free_camera.cursor_grab_enabled = snapshot.cursor_grab_enabled;
+    }
+
+    commands.remove_resource::<PhotoModeSnapshot>();
+}
+
+// Plugin that logs per-frame camera telemetry to a CSV file
+struct TelemetryPlugin;
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TelemetryLogger>()
+            .add_systems(Update, toggle_telemetry)
+            .add_systems(Last, (log_telemetry, close_telemetry_on_exit).chain());
+    }
+}
+
+#[derive(Resource)]
+struct TelemetryLogger {
+    // File the rows are written to, it is truncated every time logging starts.
+    path: PathBuf,
+    // Number of rows buffered between flushes.
+    flush_every: usize,
+    writer: Option<BufWriter<File>>,
+    unflushed_rows: usize,
+}
+
+// File the telemetry log is written to unless the config names another one.
+const TELEMETRY_PATH: &str = "camera_telemetry.csv";
+
+impl FromWorld for TelemetryLogger {
+    fn from_world(world: &mut World) -> Self {
+        let path = world
+            .get_resource::<Config>()
+            .and_then(|config| config.telemetry_path.clone())
+            .unwrap_or_else(|| PathBuf::from(TELEMETRY_PATH));
+        Self {
+            path,
+            flush_every: 120,
+            writer: None,
+            unflushed_rows: 0,
+        }
+    }
+}
+
+impl TelemetryLogger {
+    fn start(&mut self) {
+        let writer = File::create(&self.path).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            writeln!(
+                writer,
+                "time,x,y,z,velocity_right,velocity_up,velocity_forward,yaw,pitch,dt"
+            )?;
+            Ok(writer)
+        });
+        match writer {
+            Ok(writer) => {
+                info!("Logging camera telemetry to {}", self.path.display());
+                self.writer = Some(writer);
+                self.unflushed_rows = 0;
+            }
+            Err(error) => warn!(
+                "Could not start telemetry log at {}: {error}",
+                self.path.display()
+            ),
+        }
+    }
+
+    fn stop(&mut self) {
+        if let Some(mut writer) = self.writer.take() {
+            if let Err(error) = writer.flush() {
+                warn!("Could not flush telemetry log: {error}");
+            }
+            info!("Stopped logging camera telemetry");
+        }
+    }
+}
+
+fn toggle_telemetry(mut telemetry: ResMut<TelemetryLogger>, input: Res<ButtonInput<KeyCode>>) {
+    if !input.just_pressed(KeyCode::KeyL) {
+        return;
+    }
+    if telemetry.writer.is_some() {
+        telemetry.stop();
+    } else {
+        telemetry.start();
+    }
+}
+
+fn log_telemetry(
+    mut telemetry: ResMut<TelemetryLogger>,
+    time: Res<Time<Real>>,
+    camera_query: Query<(&Transform, &FreeCameraState), With<FreeCamera>>,
+) {
+    let Ok((transform, free_camera_state)) = camera_query.single() else {
+        return;
+    };
+    let TelemetryLogger {
+        writer: Some(writer),
+        flush_every,
+        unflushed_rows,
+        ..
+    } = telemetry.as_mut()
+    else {
+        return;
+    };
+
+    let position = transform.translation;
+    let velocity = free_camera_state.velocity;
+    let row = writeln!(
+        writer,
+        "{},{},{},{},{},{},{},{},{},{}",
+        time.elapsed_secs(),
+        position.x,
+        position.y,
+        position.z,
+        velocity.x,
+        velocity.y,
+        velocity.z,
+        free_camera_state.yaw,
+        free_camera_state.pitch,
+        time.delta_secs(),
+    );
+    *unflushed_rows += 1;
+    let result = row.and_then(|()| {
+        if *unflushed_rows >= *flush_every {
+            *unflushed_rows = 0;
+            writer.flush()
+        } else {
+            Ok(())
+        }
+    });
+
+    if let Err(error) = result {
+        warn!("Could not write telemetry log, stopping: {error}");
+        telemetry.writer = None;
+    }
+}
+
+// Flushes the log when the app is closing so the last rows aren't lost.
+fn close_telemetry_on_exit(
+    mut exit_events: MessageReader<AppExit>,
+    mut telemetry: ResMut<TelemetryLogger>,
+) {
+    if exit_events.read().count() > 0 {
+        telemetry.stop();
+    }
+}
+
+// Plugin that saves a numbered screenshot every few seconds while active, toggled with F10. The
+// frames end up in `timelapse/`, ready to be stitched into a video.
+struct TimelapsePlugin;
+impl Plugin for TimelapsePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Timelapse>()
+            .add_systems(PostStartup, spawn_timelapse_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (toggle_timelapse, capture_timelapse, update_timelapse_text).chain(),
+            );
+    }
+}
+
+// Directory the timelapse frames are saved to.
+const TIMELAPSE_DIR: &str = "timelapse";
+
+#[derive(Resource)]
+struct Timelapse {
+    // Seconds of real time between frames.
+    interval: f32,
+    active: bool,
+    timer: Timer,
+    // Number of the next frame, which keeps counting across toggles so frames aren't overwritten.
+    next_frame: usize,
+    // Frames written to disk so far.
+    saved: usize,
+    // Hold the auto exposure while each frame is captured, so it can't shift mid-capture.
+    fixed_exposure: bool,
+}
+
+impl Default for Timelapse {
+    fn default() -> Self {
+        Self {
+            interval: 2.0,
+            active: false,
+            timer: Timer::default(),
+            next_frame: 0,
+            saved: 0,
+            fixed_exposure: false,
+        }
+    }
+}
+
+// Tags screenshots taken for the timelapse.
+#[derive(Component)]
+struct TimelapseFrame;
+
+#[derive(Component)]
+struct TimelapseText;
+
+fn spawn_timelapse_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((TimelapseText, Text::new("")));
+}
+
+fn toggle_timelapse(
+    mut commands: Commands,
+    mut timelapse: ResMut<Timelapse>,
+    frame_query: Query<(), With<TimelapseFrame>>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    if !input.just_pressed(KeyCode::F10) {
+        return;
+    }
+
+    if timelapse.active {
+        // Screenshots already requested still get saved, they finish over the next frames
+        timelapse.active = false;
+        info!(
+            "Stopped timelapse, {} frames still being saved",
+            frame_query.iter().count()
+        );
+        return;
+    }
+    if let Err(error) = std::fs::create_dir_all(TIMELAPSE_DIR) {
+        warn!("Could not create {TIMELAPSE_DIR}: {error}");
+        return;
+    }
+    timelapse.active = true;
+    timelapse.fixed_exposure = input.pressed(KeyCode::ShiftLeft);
+    timelapse.timer = Timer::from_seconds(timelapse.interval.max(0.01), TimerMode::Repeating);
+    // Capture right away rather than an interval after pressing the key
+    take_timelapse_frame(&mut commands, &mut timelapse);
+}
+
+fn capture_timelapse(
+    mut commands: Commands,
+    mut timelapse: ResMut<Timelapse>,
+    time: Res<Time<Real>>,
+) {
+    if !timelapse.active {
+        return;
+    }
+    // A long hitch only takes one frame rather than a burst of identical ones
+    if timelapse.timer.tick(time.delta()).just_finished() {
+        take_timelapse_frame(&mut commands, &mut timelapse);
+    }
+}
+
+fn take_timelapse_frame(commands: &mut Commands, timelapse: &mut Timelapse) {
+    let path = Path::new(TIMELAPSE_DIR).join(format!("frame_{:05}.png", timelapse.next_frame));
+    timelapse.next_frame += 1;
+    let mut frame = commands.spawn((TimelapseFrame, Screenshot::primary_window()));
+    if timelapse.fixed_exposure {
+        frame.insert(FixedExposureCapture::default());
+    }
+    frame.observe(save_to_disk(path)).observe(
+        |_: On<ScreenshotCaptured>, mut timelapse: ResMut<Timelapse>| timelapse.saved += 1,
+    );
+}
+
+fn update_timelapse_text(
+    mut text: Single<&mut Text, With<TimelapseText>>,
+    timelapse: Res<Timelapse>,
+    frame_query: Query<(), With<TimelapseFrame>>,
+) {
+    let pending = frame_query.iter().count();
+    text.0 = if timelapse.active || pending > 0 {
+        format!(
+            "Timelapse: {} frames saved, {} pending",
+            timelapse.saved, pending
+        )
+    } else {
+        String::new()
+    };
+}
+
+// Plugin for a dolly zoom. Tab focuses on the collider in the middle of the view, after which the
+// field of view follows the distance to it, so the focus keeps its size on screen while the
+// camera moves towards or away from it. Tab again stops and restores the field of view.
+struct DollyZoomPlugin;
+impl Plugin for DollyZoomPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, spawn_dolly_zoom_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (toggle_dolly_zoom, apply_dolly_zoom, update_dolly_zoom_text).chain(),
+            );
+    }
+}
+
+// Keeps the frame height at the focus constant by adjusting the field of view.
+#[derive(Component)]
+struct DollyZoom {
+    // Distance from the camera to the focus, updated as the camera moves.
+    focus_distance: f32,
+    focus: Vec3,
+    // Height of the view at the focus, which the field of view keeps constant.
+    frame_height: f32,
+    // Field of view to go back to when the dolly zoom stops.
+    base_fov: f32,
+}
+
+#[derive(Component)]
+struct DollyZoomText;
+
+fn spawn_dolly_zoom_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((DollyZoomText, Text::new("")));
+}
+
+fn toggle_dolly_zoom(
+    mut commands: Commands,
+    mut camera: Single<(Entity, &Transform, &mut Projection, Option<&DollyZoom>), With<FreeCamera>>,
+    raycast: SceneRaycast,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    if !input.just_pressed(KeyCode::Tab) {
+        return;
+    }
+    let (entity, transform, projection, dolly_zoom) = &mut *camera;
+    let Projection::Perspective(perspective) = &mut **projection else {
+        warn!("Dolly zoom needs a perspective projection");
+        return;
+    };
+
+    if let Some(dolly_zoom) = dolly_zoom {
+        perspective.fov = dolly_zoom.base_fov;
+        commands.entity(*entity).remove::<DollyZoom>();
+        return;
+    }
+
+    // Focus on the collider in the middle of the view
+    let Some(hit) = raycast.raycast_scene(transform.translation, transform.forward()) else {
+        warn!("Nothing in the middle of the view to focus the dolly zoom on");
+        return;
+    };
+    commands.entity(*entity).insert(DollyZoom {
+        focus_distance: hit.distance,
+        focus: hit.point,
+        frame_height: 2.0 * hit.distance * (perspective.fov / 2.0).tan(),
+        base_fov: perspective.fov,
+    });
+}
+
+fn apply_dolly_zoom(mut camera_query: Query<(&Transform, &mut Projection, &mut DollyZoom)>) {
+    for (transform, mut projection, mut dolly_zoom) in &mut camera_query {
+        let Projection::Perspective(perspective) = &mut *projection else {
+            continue;
+        };
+        dolly_zoom.focus_distance = transform
+            .translation
+            .distance(dolly_zoom.focus)
+            .max(perspective.near);
+        perspective.fov = (2.0
+            * (dolly_zoom.frame_height / (2.0 * dolly_zoom.focus_distance)).atan())
+        .clamp(1f32.to_radians(), 170f32.to_radians());
+    }
+}
+
+fn update_dolly_zoom_text(
+    mut text: Single<&mut Text, With<DollyZoomText>>,
+    camera: Single<(&Projection, Option<&DollyZoom>), With<FreeCamera>>,
+) {
+    text.0 = match *camera {
+        (Projection::Perspective(perspective), Some(dolly_zoom)) => format!(
+            "Dolly zoom: focus {:.02} away, FOV {:.01}°",
+            dolly_zoom.focus_distance,
+            perspective.fov.to_degrees()
+        ),
+        _ => String::new(),
+    };
+}
+
+// Plugin that saves the window position and size to the config on exit, for `main` to restore
+// on the next launch.
+struct WindowGeometryPlugin;
+impl Plugin for WindowGeometryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (keep_window_on_screen, track_window_geometry))
+            .add_systems(Last, save_window_geometry);
+    }
+}
+
+// Smallest part of the window, in physical pixels along each axis, that has to be on a monitor
+// for a restored position to count as visible.
+const MIN_VISIBLE_WINDOW_SIZE: i32 = 64;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct WindowGeometry {
+    // Physical position of the top left corner, `None` if the OS placed the window.
+    position: Option<IVec2>,
+    // Logical size of the client area.
+    size: UVec2,
+}
+
+impl WindowGeometry {
+    fn apply(&self, window: &mut Window) {
+        if let Some(position) = self.position {
+            window.position = WindowPosition::At(position);
+        }
+        window.resolution = WindowResolution::new(self.size.x.max(1), self.size.y.max(1));
+    }
+}
+
+// Moves a restored window back onto the primary monitor if it would open off screen, e.g. on a
+// monitor that has since been unplugged. Monitors are only known once the app runs, so this
+// waits for them and then checks once.
+fn keep_window_on_screen(
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+    monitor_query: Query<&Monitor>,
+    mut checked: Local<bool>,
+) {
+    if *checked || monitor_query.is_empty() {
+        return;
+    }
+    *checked = true;
+
+    let WindowPosition::At(position) = window.position else {
+        return;
+    };
+    let size = window.physical_size().as_ivec2();
+    let visible = monitor_query.iter().any(|monitor| {
+        let min = monitor.physical_position;
+        let max = min + monitor.physical_size().as_ivec2();
+        let overlap = (position + size).min(max) - position.max(min);
+        overlap.cmpge(IVec2::splat(MIN_VISIBLE_WINDOW_SIZE)).all()
+    });
+    if !visible {
+        warn!("The saved window position {position} is off screen, centering the window");
+        window.position = WindowPosition::Centered(MonitorSelection::Primary);
+    }
+}
+
+// Remembers the latest geometry, since the window may already be gone by the time the app
+// exits.
+fn track_window_geometry(
+    mut config: ResMut<Config>,
+    window_query: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+) {
+    let Ok(window) = window_query.single() else {
+        return;
+    };
+    let position = match window.position {
+        WindowPosition::At(position) => Some(position),
+        _ => None,
+    };
+    let size = UVec2::new(
+        window.resolution.width().round() as u32,
+        window.resolution.height().round() as u32,
+    );
+    config.window = Some(WindowGeometry { position, size });
+}
+
+fn save_window_geometry(config: Res<Config>, mut exit_events: MessageReader<AppExit>) {
+    if exit_events.read().count() == 0 {
+        return;
+    }
+    let path = Path::new(CONFIG_PATH);
+    match config.save(path) {
+        Ok(()) => info!("Saved the window geometry to {}", path.display()),
+        Err(error) => warn!("Could not save the config to {}: {error}", path.display()),
+    }
+}
+
+// Plugin for blocking out a level from inside the app. Enter toggles wall placement, in which
+// clicking the floor places a wall segment snapped to the grid, scrolling changes the wall
+// height and Backspace turns it by 90°. Ctrl+Z removes the last wall placed.
+struct WallPlacementPlugin;
+impl Plugin for WallPlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WallPlacement>()
+            .add_systems(PostStartup, spawn_wall_placement_text.after(spawn_text))
+            // Takes the scroll before the camera sees it, so it doesn't change the speed too
+            .add_systems(PreUpdate, read_wall_height_scroll.after(InputSystems))
+            .add_systems(
+                Update,
+                (
+                    toggle_wall_placement,
+                    place_wall,
+                    undo_wall_placement,
+                    update_wall_placement_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+// Length and thickness of a placed wall segment, in meters.
+const PLACED_WALL_LENGTH: f32 = 2.0;
+const PLACED_WALL_THICKNESS: f32 = 0.2;
+
+// Height change per scroll line and lowest wall height, in meters.
+const PLACED_WALL_HEIGHT_STEP: f32 = 0.25;
+const PLACED_WALL_MIN_HEIGHT: f32 = 0.25;
+
+#[derive(Resource)]
+struct WallPlacement {
+    active: bool,
+    // Height of the next wall, in meters.
+    height: f32,
+    // Whether the next wall runs along the Z axis rather than the X axis.
+    rotated: bool,
+    // Walls placed so far, oldest first.
+    placed: Vec<Entity>,
+}
+
+impl Default for WallPlacement {
+    fn default() -> Self {
+        Self {
+            active: false,
+            height: 3.0,
+            rotated: false,
+            placed: Vec::new(),
+        }
+    }
+}
+
+impl WallPlacement {
+    // Readout line for the overlay, with the next wall's settings while placing.
+    fn status(&self) -> String {
+        if self.active {
+            format!(
+                "Wall placement: on ({:.02} m high, along {}, {} placed)",
+                self.height,
+                if self.rotated { "Z" } else { "X" },
+                self.placed.len()
+            )
+        } else {
+            "Wall placement: off".to_string()
+        }
+    }
+}
+
+// Marks the objects that make up the editable part of the world, the ones from a scene
+// description and the walls placed at runtime. Shift+Enter writes them to a scene description.
+#[derive(Component)]
+struct WorldEntity;
+
+#[derive(Component)]
+struct WallPlacementText;
+
+fn spawn_wall_placement_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((WallPlacementText, Text::new("")));
+}
+
+fn read_wall_height_scroll(
+    mut placement: ResMut<WallPlacement>,
+    mut accumulated_mouse_scroll: ResMut<AccumulatedMouseScroll>,
+) {
+    if !placement.active || accumulated_mouse_scroll.delta.y == 0.0 {
+        return;
+    }
+
+    let lines = match accumulated_mouse_scroll.unit {
+        MouseScrollUnit::Line => accumulated_mouse_scroll.delta.y,
+        MouseScrollUnit::Pixel => {
+            accumulated_mouse_scroll.delta.y / MouseScrollUnit::SCROLL_UNIT_CONVERSION_FACTOR
+        }
+    };
+    placement.height =
+        (placement.height + lines * PLACED_WALL_HEIGHT_STEP).max(PLACED_WALL_MIN_HEIGHT);
+    accumulated_mouse_scroll.delta = Vec2::ZERO;
+}
+
+fn toggle_wall_placement(mut placement: ResMut<WallPlacement>, input: Res<ButtonInput<KeyCode>>) {
+    // Shift+Enter exports the scene instead
+    let shift = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if input.just_pressed(KeyCode::Enter) && !shift {
+        placement.active = !placement.active;
+    }
+    if placement.active && input.just_pressed(KeyCode::Backspace) {
+        placement.rotated = !placement.rotated;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn place_wall(
+    mut commands: Commands,
+    mut placement: ResMut<WallPlacement>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut asset_cache: ResMut<AssetCache>,
+    white_material: Option<Res<WhiteMaterial>>,
+    camera: Single<(&Camera, &GlobalTransform), With<FreeCamera>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    raycast: SceneRaycast,
+    world_scale: Res<WorldScale>,
+    mouse: Res<ButtonInput<MouseButton>>,
+) {
+    if !placement.active || !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    // Aim through the cursor, or through the middle of the view while the cursor is grabbed
+    let (camera, global_transform) = *camera;
+    let ray = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(global_transform, cursor).ok())
+        .unwrap_or_else(|| Ray3d::new(global_transform.translation(), global_transform.forward()));
+    let Some(hit) = raycast.raycast_scene(ray.origin, ray.direction) else {
+        return;
+    };
+    // Only build on top of things, clicking a wall's side does nothing
+    if hit.normal != Dir3::Y {
+        return;
+    }
+
+    let step = GRID_SNAP_STEP * world_scale.0;
+    let translation = Vec3::new(
+        (hit.point.x / step).round() * step,
+        hit.point.y + placement.height * world_scale.0 / 2.0,
+        (hit.point.z / step).round() * step,
+    );
+    let rotation = if placement.rotated {
+        face_direction(Dir3::X)
+    } else {
+        Quat::IDENTITY
+    };
+    let mesh = asset_cache.mesh(
+        &mut meshes,
+        MeshKind::Cuboid {
+            x: PLACED_WALL_LENGTH,
+            y: placement.height,
+            z: PLACED_WALL_THICKNESS,
+        },
+    );
+    let material = match white_material {
+        Some(white_material) => white_material.0.clone(),
+        // Started with a scene of its own, which doesn't have the built-in walls
+        None => asset_cache.material(&mut materials, Color::WHITE),
+    };
+
+    let wall = commands
+        .spawn((
+            WorldEntity,
+            SceneMaterial,
+            WallMaterial,
+            CameraCollider::default(),
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            Transform {
+                translation,
+                rotation,
+                scale: Vec3::splat(world_scale.0),
+            },
+        ))
+        .id();
+    placement.placed.push(wall);
+}
+
+fn undo_wall_placement(
+    mut commands: Commands,
+    mut placement: ResMut<WallPlacement>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    let control = input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if !control || !input.just_pressed(KeyCode::KeyZ) {
+        return;
+    }
+    if let Some(wall) = placement.placed.pop() {
+        commands.entity(wall).try_despawn();
+    }
+}
+
+fn update_wall_placement_text(
+    mut text: Single<&mut Text, With<WallPlacementText>>,
+    placement: Res<WallPlacement>,
+) {
+    if !placement.is_changed() {
+        return;
+    }
+
+    text.0 = placement.status();
+}
+
+// Plugin that snaps the camera to the grid with the 0 key, so positions copied from the overlay
+// are round numbers.
+struct GridSnapPlugin;
+impl Plugin for GridSnapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GridSnap>()
+            .add_systems(PostStartup, spawn_grid_snap_text.after(spawn_text))
+            .add_systems(Update, (snap_camera_to_grid, update_grid_snap_text).chain());
+    }
+}
+
+// Grid spacing in meters.
+const GRID_SNAP_STEP: f32 = 0.5;
+
+// How long the snapped position stays in the overlay, in seconds.
+const GRID_SNAP_DISPLAY_TIME: f32 = 3.0;
+
+#[derive(Resource, Default)]
+struct GridSnap {
+    // Where the camera was snapped to last, and the timer for showing it.
+    last: Option<(Vec3, Timer)>,
+}
+
+#[derive(Component)]
+struct GridSnapText;
+
+fn spawn_grid_snap_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((GridSnapText, Text::new("")));
+}
+
+fn snap_camera_to_grid(
+    mut grid_snap: ResMut<GridSnap>,
+    mut camera: Single<(&mut Transform, &mut FreeCameraState)>,
+    world_scale: Res<WorldScale>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    if !input.just_pressed(KeyCode::Digit0) {
+        return;
+    }
+    let (transform, state) = &mut *camera;
+    let position = state.snap_to_grid(transform, GRID_SNAP_STEP * world_scale.0);
+    grid_snap.last = Some((
+        position,
+        Timer::from_seconds(GRID_SNAP_DISPLAY_TIME, TimerMode::Once),
+    ));
+}
+
+fn update_grid_snap_text(
+    mut text: Single<&mut Text, With<GridSnapText>>,
+    mut grid_snap: ResMut<GridSnap>,
+    time: Res<Time<Real>>,
+) {
+    let Some((position, timer)) = &mut grid_snap.last else {
+        return;
+    };
+    timer.tick(time.delta());
+    if timer.is_finished() {
+        text.0.clear();
+        grid_snap.last = None;
+    } else {
+        text.0 = format!(
+            "Snapped to: {:.02} {:.02} {:.02}",
+            position.x, position.y, position.z
+        );
+    }
+}
+
+// Plugin that bookmarks camera viewpoints. Semicolon adds one at the camera, Quote flies to the
+// next one, and F6/F7 save and load the whole set.
+struct WaypointsPlugin;
+impl Plugin for WaypointsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Waypoints>()
+            .add_systems(Update, update_waypoints);
+    }
+}
+
+// File the waypoints are saved to and loaded from.
+const WAYPOINTS_PATH: &str = "waypoints.ron";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Waypoint {
+    name: String,
+    transform: Transform,
+}
+
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+struct Waypoints {
+    bookmarks: Vec<Waypoint>,
+    // Index of the bookmark the camera went to last.
+    #[serde(skip)]
+    current: Option<usize>,
+}
+
+impl Waypoints {
+    fn save(&self, path: &Path) -> Result<(), BevyError> {
+        let ron = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, ron)?;
+        Ok(())
+    }
+
+    fn load(path: &Path) -> Result<Self, BevyError> {
+        let ron = std::fs::read_to_string(path)?;
+        Ok(ron::from_str(&ron)?)
+    }
+
+    // Replaces the waypoints with the ones saved at `path`. A broken file leaves them as they
+    // are.
+    fn reload(&mut self, path: &Path) -> Result<(), BevyError> {
+        *self = Self::load(path)?;
+        Ok(())
+    }
+}
+
+fn update_waypoints(
+    mut waypoints: ResMut<Waypoints>,
+    mut camera: Single<(&mut Transform, &FreeCamera, &mut FreeCameraState)>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    let (transform, free_camera, state) = &mut *camera;
+    let path = Path::new(WAYPOINTS_PATH);
+
+    if input.just_pressed(KeyCode::Semicolon) {
+        let name = format!("Waypoint {}", waypoints.bookmarks.len() + 1);
+        info!("Added {name}");
+        waypoints.bookmarks.push(Waypoint {
+            name,
+            transform: **transform,
+        });
+    }
+
+    if input.just_pressed(KeyCode::Quote) && !waypoints.bookmarks.is_empty() {
+        let index = waypoints
+            .current
+            .map_or(0, |index| (index + 1) % waypoints.bookmarks.len());
+        waypoints.current = Some(index);
+        let waypoint = &waypoints.bookmarks[index];
+        **transform = waypoint.transform;
+        // Look the way the waypoint faces, or the next mouse motion turns the camera back
+        state.set_look_rotation(free_camera, waypoint.transform.rotation);
+        state.velocity = Vec3::ZERO;
+        info!("Went to {}", waypoint.name);
+    }
+
+    if input.just_pressed(KeyCode::F6) {
+        match waypoints.save(path) {
+            Ok(()) => info!(
+                "Saved {} waypoints to {}",
+                waypoints.bookmarks.len(),
+                path.display()
+            ),
+            Err(error) => warn!("Could not save waypoints to {}: {error}", path.display()),
+        }
+    }
+
+    if input.just_pressed(KeyCode::F7) {
+        match waypoints.reload(path) {
+            Ok(()) => info!(
+                "Loaded {} waypoints from {}",
+                waypoints.bookmarks.len(),
+                path.display()
+            ),
+            Err(error) => warn!("Could not load waypoints from {}: {error}", path.display()),
+        }
+    }
+}
+
+// Plugin that slowly circles the camera around the spot it is looking at after a while without
+// input, like a screensaver. Any input hands control back right away.
+struct AutoOrbitPlugin;
+impl Plugin for AutoOrbitPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutoOrbit>()
+            .init_resource::<AutoOrbitState>()
+            .add_systems(PostStartup, spawn_auto_orbit_text.after(spawn_text))
+            .add_systems(Update, (update_auto_orbit, update_auto_orbit_text).chain());
+    }
+}
+
+#[derive(Resource)]
+struct AutoOrbit {
+    // Seconds without input before the camera starts orbiting.
+    idle_timeout: f32,
+    // Orbit speed in radians per second.
+    speed: f32,
+    // Distance from the camera to the point it orbits, in meters.
+    radius: f32,
+}
+
+impl Default for AutoOrbit {
+    fn default() -> Self {
+        Self {
+            idle_timeout: 60.0,
+            speed: 0.2,
+            radius: 8.0,
+        }
+    }
+}
+
+// Seconds the camera takes to blend from where it was into the orbit.
+const AUTO_ORBIT_BLEND_TIME: f32 = 2.0;
+
+#[derive(Resource, Default)]
+struct AutoOrbitState {
+    idle_time: f32,
+    orbit: Option<Orbit>,
+}
+
+struct Orbit {
+    focus: Vec3,
+    radius: f32,
+    angle: f32,
+    // Pose the camera had when the orbit started, blended out over `AUTO_ORBIT_BLEND_TIME`.
+    start: Transform,
+    elapsed: f32,
+}
+
+#[derive(Component)]
+struct AutoOrbitText;
+
+fn spawn_auto_orbit_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((AutoOrbitText, Text::new("")));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_auto_orbit(
+    auto_orbit: Res<AutoOrbit>,
+    mut state: ResMut<AutoOrbitState>,
+    mut camera: Single<(&mut Transform, &FreeCamera, &mut FreeCameraState)>,
+    mut keyboard_events: MessageReader<KeyboardInput>,
+    mut mouse_button_events: MessageReader<MouseButtonInput>,
+    mut mouse_motion_events: MessageReader<MouseMotion>,
+    mut mouse_wheel_events: MessageReader<MouseWheel>,
+    world_scale: Res<WorldScale>,
+    time: Res<Time<Real>>,
+) {
+    let (transform, free_camera, camera_state) = &mut *camera;
+
+    let input = keyboard_events.read().count()
+        + mouse_button_events.read().count()
+        + mouse_motion_events.read().count()
+        + mouse_wheel_events.read().count()
+        > 0;
+    if input {
+        state.idle_time = 0.0;
+        if state.orbit.take().is_some() {
+            // Carry on from wherever the orbit left the camera
+            camera_state.set_look_rotation(free_camera, transform.rotation);
+        }
+        return;
+    }
+
+    let dt = time.delta_secs();
+    state.idle_time += dt;
+    if state.orbit.is_none() && state.idle_time >= auto_orbit.idle_timeout {
+        // Orbit the point straight ahead, at the camera's height
+        let radius = auto_orbit.radius * world_scale.0;
+        let forward = transform
+            .forward()
+            .with_y(0.0)
+            .try_normalize()
+            .unwrap_or(Vec3::NEG_Z);
+        let offset = -forward * radius;
+        state.orbit = Some(Orbit {
+            focus: transform.translation - offset,
+            radius,
+            angle: offset.z.atan2(offset.x),
+            start: **transform,
+            elapsed: 0.0,
+        });
+    }
+
+    let Some(orbit) = &mut state.orbit else {
+        return;
+    };
+    orbit.angle += auto_orbit.speed * dt;
+    orbit.elapsed += dt;
+
+    let position =
+        orbit.focus + Vec3::new(orbit.angle.cos(), 0.0, orbit.angle.sin()) * orbit.radius;
+    let target = Transform::from_translation(position).looking_at(orbit.focus, Vec3::Y);
+    let t = (orbit.elapsed / AUTO_ORBIT_BLEND_TIME).min(1.0);
+    let blend = apply_ease(EaseKind::Smoothstep, t);
+    transform.translation = orbit.start.translation.lerp(target.translation, blend);
+    transform.rotation = orbit.start.rotation.slerp(target.rotation, blend);
+    // Keep gravity and leftover speed from dragging the camera off the orbit
+    camera_state.velocity = Vec3::ZERO;
+}
+
+fn update_auto_orbit_text(
+    mut text: Single<&mut Text, With<AutoOrbitText>>,
+    state: Res<AutoOrbitState>,
+) {
+    let idle = if state.orbit.is_some() { "IDLE" } else { "" };
+    if text.0 != idle {
+        text.0 = idle.to_string();
+    }
+}
+
+// Plugin that points the camera at the landmarks in the scene one after another with backslash,
+// and back to free look after the last one.
+struct LandmarkPlugin;
+impl Plugin for LandmarkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, cycle_look_target);
+    }
+}
+
+fn cycle_look_target(
+    mut commands: Commands,
+    camera: Single<(Entity, Option<&LookAtTarget>), With<FreeCamera>>,
+    landmark_query: Query<Entity, With<Landmark>>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    if !input.just_pressed(KeyCode::Backslash) {
+        return;
+    }
+    let (camera, look_target) = *camera;
+
+    let mut landmarks: Vec<Entity> = landmark_query.iter().collect();
+    landmarks.sort();
+    let next = match look_target {
+        Some(look_target) => landmarks
+            .iter()
+            .position(|landmark| *landmark == look_target.entity)
+            .and_then(|index| landmarks.get(index + 1)),
+        None => landmarks.first(),
+    };
+    match next {
+        Some(landmark) => {
+            commands.entity(camera).insert(LookAtTarget::new(*landmark));
+        }
+        None => {
+            commands.entity(camera).remove::<LookAtTarget>();
+        }
+    }
+}
+
+// Plugin that fills the world with cubes to benchmark rendering
+struct StressTestPlugin;
+impl Plugin for StressTestPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FrameTimeDiagnosticsPlugin::default())
+            .register_diagnostic(Diagnostic::new(VISIBLE_MESHES))
+            .register_diagnostic(Diagnostic::new(MESH_BATCHES))
+            .register_diagnostic(Diagnostic::new(CULLED_MESHES))
+            .init_resource::<StressTest>()
+            .add_systems(PostStartup, spawn_stress_test_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (
+                    spawn_stress_cubes,
+                    despawn_stress_cubes,
+                    measure_mesh_batches,
+                    update_stress_test_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+// Number of meshes that were visible last frame.
+const VISIBLE_MESHES: DiagnosticPath = DiagnosticPath::const_new("visible_meshes");
+// Number of meshes skipped by frustum culling last frame. Meshes rejected by GPU occlusion
+// culling are not included, finding those would need a readback from the GPU.
+const CULLED_MESHES: DiagnosticPath = DiagnosticPath::const_new("culled_meshes");
+// Number of distinct mesh and material pairs among the visible meshes. Bevy batches each pair
+// into instanced draws, so this approximates the draw calls of the main pass.
+const MESH_BATCHES: DiagnosticPath = DiagnosticPath::const_new("mesh_batches");
+
+#[derive(Resource)]
+struct StressTest {
+    // Number of cubes spawned by each press of H.
+    count: usize,
+    // Distance between neighbouring cubes, before the world scale is applied.
+    spacing: f32,
+}
+
+impl Default for StressTest {
+    fn default() -> Self {
+        Self {
+            count: 5000,
+            spacing: 1.5,
+        }
+    }
+}
+
+impl StressTest {
+    // Position of the cube with the given index. Cubes fill the floor area row by row,
+    // stacking further layers above the walls once a layer is full.
+    fn position(&self, index: usize) -> Vec3 {
+        let columns = (36.0 / self.spacing) as usize + 1;
+        let rows = (66.0 / self.spacing) as usize + 1;
+        let layer = index / (columns * rows);
+        let index = index % (columns * rows);
+        Vec3::new(
+            -18.0 + (index % columns) as f32 * self.spacing,
+            6.0 + layer as f32 * self.spacing,
+            -33.0 + (index / columns) as f32 * self.spacing,
+        )
+    }
+}
+
+// Tags stress test cubes so they can be cleared without touching the rest of the world.
+#[derive(Component)]
+struct StressCube;
+
+#[derive(Component)]
+struct StressTestText;
+
+fn spawn_stress_test_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((StressTestText, Text::new("")));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_stress_cubes(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut asset_cache: ResMut<AssetCache>,
+    stress_test: Res<StressTest>,
+    world_scale: Res<WorldScale>,
+    cube_query: Query<Entity, With<StressCube>>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    if !input.just_pressed(KeyCode::KeyH) {
+        return;
+    }
+
+    // Start over so repeated presses don't pile up cubes in the same spots
+    for entity in &cube_query {
+        commands.entity(entity).despawn();
+    }
+
+    let material = materials.add(Color::from(tailwind::EMERALD_500));
+    let cube = asset_cache.mesh(
+        &mut meshes,
+        MeshKind::Cuboid {
+            x: 1.0,
+            y: 1.0,
+            z: 1.0,
+        },
+    );
+    let cubes: Vec<_> = (0..stress_test.count)
+        .map(|index| {
+            (
+                StressCube,
+                Mesh3d(cube.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::from_translation(stress_test.position(index) * world_scale.0)
+                    .with_scale(Vec3::splat(world_scale.0)),
+            )
+        })
+        .collect();
+    commands.spawn_batch(cubes);
+}
+
+fn despawn_stress_cubes(
+    mut commands: Commands,
+    cube_query: Query<Entity, With<StressCube>>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    if !input.just_pressed(KeyCode::KeyJ) {
+        return;
+    }
+
+    for entity in &cube_query {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn measure_mesh_batches(
+    mut diagnostics: Diagnostics,
+    mesh_query: Query<(&Mesh3d, &MeshMaterial3d<StandardMaterial>, &ViewVisibility)>,
+) {
+    let total = mesh_query.iter().len();
+    let visible: Vec<_> = mesh_query
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .map(|(mesh, material, _)| (mesh.id(), material.id()))
+        .collect();
+    let batches: HashSet<_> = visible.iter().collect();
+
+    diagnostics.add_measurement(&VISIBLE_MESHES, || visible.len() as f64);
+    diagnostics.add_measurement(&MESH_BATCHES, || batches.len() as f64);
+    diagnostics.add_measurement(&CULLED_MESHES, || (total - visible.len()) as f64);
+}
+
+fn update_stress_test_text(
+    mut text: Single<&mut Text, With<StressTestText>>,
+    diagnostics: Res<DiagnosticsStore>,
+    cube_query: Query<(), With<StressCube>>,
+) {
+    let value = |path| {
+        diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .unwrap_or_default()
+    };
+
+    text.0 = format!(
+        "Stress cubes: {} (FPS {:.0}, culled {:.0})\nVisible meshes: {:.0} in {:.0} batches",
+        cube_query.iter().len(),
+        value(&FrameTimeDiagnosticsPlugin::FPS),
+        value(&CULLED_MESHES),
+        value(&VISIBLE_MESHES),
+        value(&MESH_BATCHES),
+    );
+}
+
+// Plugin that toggles GPU occlusion culling, which skips walls hidden behind other walls
+struct OcclusionCullingPlugin;
+impl Plugin for OcclusionCullingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OcclusionSettings>()
+            .add_systems(PostStartup, spawn_occlusion_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (toggle_occlusion_culling, update_occlusion_text).chain(),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+struct OcclusionSettings {
+    enabled: bool,
+}
+
+#[derive(Component)]
+struct OcclusionText;
+
+fn spawn_occlusion_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((OcclusionText, Text::new("")));
+}
+
+fn toggle_occlusion_culling(
+    mut commands: Commands,
+    mut occlusion: ResMut<OcclusionSettings>,
+    camera_query: Query<(Entity, Has<MotionBlur>), With<FreeCamera>>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    if !input.just_pressed(KeyCode::KeyO) {
+        return;
+    }
+    occlusion.enabled = !occlusion.enabled;
+
+    for (camera, motion_blur) in &camera_query {
+        if occlusion.enabled {
+            // Occlusion culling is ignored without a depth prepass
+            commands
+                .entity(camera)
+                .insert((DepthPrepass, OcclusionCulling));
+        } else {
+            commands.entity(camera).remove::<OcclusionCulling>();
+            // Motion blur still needs the depth prepass
+            if !motion_blur {
+                commands.entity(camera).remove::<DepthPrepass>();
+            }
+        }
+    }
+}
+
+fn update_occlusion_text(
+    mut text: Single<&mut Text, With<OcclusionText>>,
+    occlusion: Res<OcclusionSettings>,
+) {
+    if occlusion.is_changed() {
+        text.0 = format!("Occlusion culling: {}", occlusion.enabled);
+    }
+}
+
+// Plugin that swaps meshes for simpler ones as they get further from the camera
+struct LodPlugin;
+impl Plugin for LodPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_lod);
+    }
+}
+
+fn update_lod(
+    camera: Single<&GlobalTransform, With<FreeCamera>>,
+    mut lod_query: Query<(&mut Lod, &mut Mesh3d, &mut Visibility, &GlobalTransform)>,
+) {
+    let camera_position = camera.translation();
+    for (mut lod, mut mesh, mut visibility, global_transform) in &mut lod_query {
+        let level = lod.level_at(camera_position.distance(global_transform.translation()));
+        if level == lod.level {
+            continue;
+        }
+        lod.level = level;
+
+        match lod.meshes.get(level) {
+            Some(handle) => {
+                mesh.0 = handle.clone();
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+// Plugin that cycles between anti-aliasing methods to compare their quality and cost
+struct AntiAliasingPlugin;
+impl Plugin for AntiAliasingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AntiAliasing>()
+            .add_systems(PostStartup, spawn_anti_aliasing_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (cycle_anti_aliasing, update_anti_aliasing_text).chain(),
+            );
+    }
+}
+
+// Anti-aliasing method used by the camera, MSAA by default like a fresh Bevy camera.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum AntiAliasing {
+    #[default]
+    Msaa,
+    Fxaa,
+    Taa,
+}
+
+impl AntiAliasing {
+    fn next(self) -> Self {
+        match self {
+            AntiAliasing::Msaa => AntiAliasing::Fxaa,
+            AntiAliasing::Fxaa => AntiAliasing::Taa,
+            AntiAliasing::Taa => AntiAliasing::Msaa,
+        }
+    }
+}
+
+#[derive(Component)]
+struct AntiAliasingText;
+
+fn spawn_anti_aliasing_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((AntiAliasingText, Text::new("")));
+}
+
+fn cycle_anti_aliasing(
+    mut commands: Commands,
+    mut anti_aliasing: ResMut<AntiAliasing>,
+    camera_query: Query<(Entity, Has<MotionBlur>, Has<OcclusionCulling>), With<FreeCamera>>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    if !input.just_pressed(KeyCode::KeyI) {
+        return;
+    }
+    *anti_aliasing = anti_aliasing.next();
+
+    for (camera, motion_blur, occlusion_culling) in &camera_query {
+        let mut camera = commands.entity(camera);
+        // MSAA conflicts with both post-process methods, so it is only on when selected
+        camera.remove::<(Fxaa, TemporalAntiAliasing)>();
+        match *anti_aliasing {
+            AntiAliasing::Msaa => {
+                camera.insert(Msaa::Sample4);
+            }
+            AntiAliasing::Fxaa => {
+                camera.insert((Msaa::Off, Fxaa::default()));
+            }
+            AntiAliasing::Taa => {
+                // Inserting TAA also pulls in the depth and motion vector prepasses it needs
+                camera.insert((Msaa::Off, TemporalAntiAliasing::default()));
+                warn!("TAA smears alpha-blended materials such as the marble and floor");
+            }
+        }
+
+        // Drop what TAA pulled in, keeping the prepasses other effects still rely on
+        if *anti_aliasing != AntiAliasing::Taa {
+            camera.remove::<(TemporalJitter, MipBias)>();
+            if !motion_blur {
+                camera.remove::<MotionVectorPrepass>();
+            }
+            if !motion_blur && !occlusion_culling {
+                camera.remove::<DepthPrepass>();
+            }
+        }
+    }
+}
+
+fn update_anti_aliasing_text(
+    mut text: Single<&mut Text, With<AntiAliasingText>>,
+    anti_aliasing: Res<AntiAliasing>,
+) {
+    if anti_aliasing.is_changed() {
+        text.0 = match *anti_aliasing {
+            AntiAliasing::Msaa => "Anti-aliasing: MSAA x4".to_string(),
+            AntiAliasing::Fxaa => "Anti-aliasing: FXAA".to_string(),
+            AntiAliasing::Taa => "Anti-aliasing: TAA".to_string(),
+        };
+    }
+}
+
+// Plugin that renders the scene at a fraction of the window resolution and upscales it. The 3D
+// camera draws into an image shown by a full-screen UI node, while the UI gets its own camera
+// so text stays sharp at any scale. Only active with the image render target from the config.
+struct RenderScalePlugin;
+impl Plugin for RenderScalePlugin {
+    fn build(&self, app: &mut App) {
+        let offscreen = |config: Res<Config>| config.render_target == RenderTargetConfig::Image;
+        app.init_resource::<RenderScale>()
+            .add_systems(
+                Startup,
+                spawn_scene_view.after(spawn_camera).run_if(offscreen),
+            )
+            .add_systems(
+                PostStartup,
+                spawn_render_scale_text.after(spawn_text).run_if(offscreen),
+            )
+            .add_systems(
+                Update,
+                (
+                    step_render_scale,
+                    resize_scene_view,
+                    update_render_scale_text,
+                )
+                    .chain()
+                    .run_if(offscreen),
+            );
+    }
+}
+
+// Fraction of the window resolution the scene is rendered at.
+#[derive(Resource)]
+struct RenderScale(f32);
+
+impl Default for RenderScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl RenderScale {
+    const MIN: f32 = 0.25;
+    const MAX: f32 = 2.0;
+    const STEP: f32 = 0.25;
+
+    // Internal resolution for a window with the given physical size.
+    fn resolution(&self, window_size: UVec2) -> UVec2 {
+        (window_size.as_vec2() * self.0)
+            .round()
+            .as_uvec2()
+            .max(UVec2::ONE)
+    }
+}
+
+// Full-screen node showing the image the 3D camera renders into.
+#[derive(Component)]
+struct SceneView;
+
+// The offscreen image the 3D camera renders into, with the image render target.
+#[derive(Resource)]
+struct SceneImage(Handle<Image>);
+
+#[derive(Component)]
+struct RenderScaleText;
+
+fn spawn_scene_view(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    camera: Single<Entity, With<FreeCamera>>,
+) {
+    // Sized properly on the first update, once the window size is known
+    let image = images.add(Image::new_target_texture(
+        1,
+        1,
+        TextureFormat::Bgra8UnormSrgb,
+        None,
+    ));
+
+    commands
+        .entity(*camera)
+        .insert(RenderTarget::Image(image.clone().into()));
+    commands.insert_resource(SceneImage(image.clone()));
+    commands.spawn((
+        Camera2d,
+        Camera {
+            order: 1,
+            ..default()
+        },
+        IsDefaultUiCamera,
+    ));
+    commands.spawn((
+        SceneView,
+        ImageNode::new(image),
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
+            ..default()
+        },
+        // Behind everything else, including the pause overlay
+        GlobalZIndex(-10),
+        FocusPolicy::Pass,
+        Pickable::IGNORE,
+    ));
+}
+
+fn spawn_render_scale_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((RenderScaleText, Text::new("")));
+}
+
+fn step_render_scale(mut render_scale: ResMut<RenderScale>, input: Res<ButtonInput<KeyCode>>) {
+    if input.just_pressed(KeyCode::Minus) {
+        render_scale.0 = (render_scale.0 - RenderScale::STEP).max(RenderScale::MIN);
+    }
+    if input.just_pressed(KeyCode::Equal) {
+        render_scale.0 = (render_scale.0 + RenderScale::STEP).min(RenderScale::MAX);
+    }
+}
+
+// Keeps the render image in step with the window size and the render scale.
+fn resize_scene_view(
+    render_scale: Res<RenderScale>,
+    window: Single<&Window>,
+    scene_image: Res<SceneImage>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let resolution = render_scale.resolution(window.physical_size());
+    if images
+        .get(&scene_image.0)
+        .is_some_and(|image| image.size() != resolution)
+        && let Some(image) = images.get_mut(&scene_image.0)
+    {
+        image.resize(Extent3d {
+            width: resolution.x,
+            height: resolution.y,
+            ..default()
+        });
+    }
+}
+
+fn update_render_scale_text(
+    mut text: Single<&mut Text, With<RenderScaleText>>,
+    render_scale: Res<RenderScale>,
+    window: Single<&Window>,
+) {
+    let resolution = render_scale.resolution(window.physical_size());
+    text.0 = format!(
+        "Render scale: {:.02} ({}x{})",
+        render_scale.0, resolution.x, resolution.y
+    );
+}
+
+// Plugin that draws the background as a vertical gradient on a large dome around the camera,
+// so the scene doesn't need the skybox texture
+struct GradientSkyPlugin;
+impl Plugin for GradientSkyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GradientSky>()
+            .add_systems(Startup, spawn_gradient_sky)
+            .add_systems(
+                Update,
+                (
+                    tweak_gradient_sky,
+                    update_gradient_sky,
+                    follow_camera_with_sky,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct GradientSky {
+    enabled: bool,
+    // Color at and below the horizon.
+    horizon: Color,
+    // Color straight up.
+    zenith: Color,
+    // Hue change per second while a tweak key is held, in degrees.
+    hue_speed: f32,
+}
+
+impl Default for GradientSky {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            horizon: Color::srgb(0.78, 0.87, 0.95),
+            zenith: Color::srgb(0.22, 0.47, 0.85),
+            hue_speed: 60.0,
+        }
+    }
+}
+
+impl GradientSky {
+    // Sky color in the given direction from the camera.
+    fn color(&self, direction: Vec3) -> LinearRgba {
+        let height = direction.normalize_or_zero().y.max(0.0);
+        LinearRgba::from(self.horizon).mix(&LinearRgba::from(self.zenith), height)
+    }
+}
+
+// Dome the gradient sky is drawn on, kept centered on the camera.
+#[derive(Component)]
+struct GradientSkyDome;
+
+fn spawn_gradient_sky(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    world_scale: Res<WorldScale>,
+) {
+    commands.spawn((
+        GradientSkyDome,
+        Mesh3d(meshes.add(Sphere::new(1.0).mesh().uv(32, 16))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            unlit: true,
+            // Seen from the inside
+            cull_mode: None,
+            ..default()
+        })),
+        // Well inside the camera's far plane
+        Transform::from_scale(Vec3::splat(500.0 * world_scale.0)),
+        NotShadowCaster,
+    ));
+}
+
+fn tweak_gradient_sky(
+    mut sky: ResMut<GradientSky>,
+    input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+) {
+    if input.just_pressed(KeyCode::KeyR) {
+        sky.enabled = !sky.enabled;
+    }
+
+    let step = sky.hue_speed * time.delta_secs();
+    let zenith_shift = match (
+        input.pressed(KeyCode::Digit1),
+        input.pressed(KeyCode::Digit2),
+    ) {
+        (true, false) => -step,
+        (false, true) => step,
+        _ => 0.0,
+    };
+    let horizon_shift = match (
+        input.pressed(KeyCode::Digit3),
+        input.pressed(KeyCode::Digit4),
+    ) {
+        (true, false) => -step,
+        (false, true) => step,
+        _ => 0.0,
+    };
+    if zenith_shift != 0.0 {
+        sky.zenith = sky.zenith.rotate_hue(zenith_shift);
+    }
+    if horizon_shift != 0.0 {
+        sky.horizon = sky.horizon.rotate_hue(horizon_shift);
+    }
+}
+
+// Writes the gradient into the dome's vertex colors and switches between the two skies.
+fn update_gradient_sky(
+    sky: Res<GradientSky>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut dome_query: Query<(&Mesh3d, &mut Visibility), With<GradientSkyDome>>,
+    mut skybox_query: Query<&mut Visibility, (With<SkyboxCircle>, Without<GradientSkyDome>)>,
+) {
+    if !sky.is_changed() {
+        return;
+    }
+
+    for (mesh, mut visibility) in &mut dome_query {
+        *visibility = if sky.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        let Some(mesh) = meshes.get_mut(mesh) else {
+            continue;
+        };
+        let Some(positions) = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .and_then(|positions| positions.as_float3())
+        else {
+            continue;
+        };
+        let colors: Vec<[f32; 4]> = positions
+            .iter()
+            .map(|position| sky.color(Vec3::from(*position)).to_f32_array())
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+
+    for mut visibility in &mut skybox_query {
+        *visibility = if sky.enabled {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+fn follow_camera_with_sky(
+    camera: Single<&Transform, With<FreeCamera>>,
+    mut dome: Single<&mut Transform, (With<GradientSkyDome>, Without<FreeCamera>)>,
+) {
+    dome.translation = camera.translation;
+}
+
+// Plugin that forces the scene materials lit or unlit, to preview how the light affects them
+struct MaterialLightingPlugin;
+impl Plugin for MaterialLightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MaterialLighting>()
+            .add_systems(PostStartup, spawn_material_lighting_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (
+                    cycle_material_lighting,
+                    apply_material_lighting,
+                    update_material_lighting_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+struct MaterialLighting {
+    mode: LightingMode,
+    // `unlit` flags as authored, saved the first time a material is overridden.
+    authored: HashMap<AssetId<StandardMaterial>, bool>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum LightingMode {
+    #[default]
+    Authored,
+    Lit,
+    Unlit,
+}
+
+#[derive(Component)]
+struct MaterialLightingText;
+
+fn spawn_material_lighting_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((MaterialLightingText, Text::new("")));
+}
+
+fn cycle_material_lighting(
+    mut lighting: ResMut<MaterialLighting>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    if input.just_pressed(KeyCode::KeyU) {
+        lighting.mode = match lighting.mode {
+            LightingMode::Authored => LightingMode::Lit,
+            LightingMode::Lit => LightingMode::Unlit,
+            LightingMode::Unlit => LightingMode::Authored,
+        };
+    }
+}
+
+fn apply_material_lighting(
+    mut lighting: ResMut<MaterialLighting>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    material_query: Query<&MeshMaterial3d<StandardMaterial>, With<SceneMaterial>>,
+) {
+    if !lighting.is_changed() {
+        return;
+    }
+
+    let mode = lighting.mode;
+    let ids: HashSet<_> = material_query
+        .iter()
+        .map(|material| material.id())
+        .collect();
+    for id in ids {
+        let Some(material) = materials.get_mut(id) else {
+            continue;
+        };
+        let authored = *lighting.authored.entry(id).or_insert(material.unlit);
+        let unlit = match mode {
+            LightingMode::Authored => authored,
+            LightingMode::Lit => false,
+            LightingMode::Unlit => true,
+        };
+        if material.unlit != unlit {
+            material.unlit = unlit;
+        }
+    }
+}
+
+fn update_material_lighting_text(
+    mut text: Single<&mut Text, With<MaterialLightingText>>,
+    lighting: Res<MaterialLighting>,
+) {
+    if lighting.is_changed() {
+        text.0 = format!("Materials: {:?}", lighting.mode);
+    }
+}
+
+// Plugin that turns backface culling of the scene materials off with F9, so the thin walls can
+// be seen from either side while checking which way they face.
+struct BackfaceCullingPlugin;
+impl Plugin for BackfaceCullingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BackfaceCulling>()
+            .add_systems(PostStartup, spawn_backface_culling_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (
+                    toggle_backface_culling,
+                    apply_backface_culling,
+                    update_backface_culling_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct BackfaceCulling {
+    enabled: bool,
+}
+
+impl Default for BackfaceCulling {
+    fn default() -> Self {
+        // Same as a default StandardMaterial
+        Self { enabled: true }
+    }
+}
+
+#[derive(Component)]
+struct BackfaceCullingText;
+
+fn spawn_backface_culling_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((BackfaceCullingText, Text::new("")));
+}
+
+fn toggle_backface_culling(mut culling: ResMut<BackfaceCulling>, input: Res<ButtonInput<KeyCode>>) {
+    if input.just_pressed(KeyCode::F9) {
+        culling.enabled = !culling.enabled;
+    }
+}
+
+fn apply_backface_culling(
+    culling: Res<BackfaceCulling>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    material_query: Query<&MeshMaterial3d<StandardMaterial>, With<SceneMaterial>>,
+) {
+    if !culling.is_changed() {
+        return;
+    }
+
+    let cull_mode = culling.enabled.then_some(Face::Back);
+    let ids: HashSet<_> = material_query
+        .iter()
+        .map(|material| material.id())
+        .collect();
+    for id in ids {
+        let Some(material) = materials.get_mut(id) else {
+            continue;
+        };
+        if material.cull_mode != cull_mode {
+            material.cull_mode = cull_mode;
+        }
+    }
+}
+
+fn update_backface_culling_text(
+    mut text: Single<&mut Text, With<BackfaceCullingText>>,
+    culling: Res<BackfaceCulling>,
+) {
+    if culling.is_changed() {
+        text.0 = format!("Backface culling: {}", culling.enabled);
+    }
+}
+
+// Plugin that trades shadow quality for performance. Slash cycles the shadow map resolution,
+// F11 and F12 the cascade count and distance of directional lights.
+struct ShadowSettingsPlugin;
+impl Plugin for ShadowSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShadowSettings>()
+            .add_systems(PostStartup, spawn_shadow_settings_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (
+                    cycle_shadow_settings,
+                    apply_shadow_settings,
+                    update_shadow_settings_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+// Shadow map resolutions to cycle through. Shadow maps need power of two sizes.
+const SHADOW_MAP_SIZES: [usize; 6] = [256, 512, 1024, 2048, 4096, 8192];
+// Largest point light shadow map, a cube map of six 8192 faces would take gigabytes.
+const MAX_POINT_SHADOW_MAP_SIZE: usize = 4096;
+// Cascade counts to cycle through, four is the most Bevy supports.
+const SHADOW_CASCADE_COUNTS: [usize; 4] = [1, 2, 3, 4];
+// Distances, in meters, up to which directional lights cast shadows.
+const SHADOW_DISTANCES: [f32; 5] = [50.0, 100.0, 150.0, 200.0, 300.0];
+
+// Marks the lights the shadow settings apply to.
+#[derive(Component)]
+struct ShadowLight;
+
+#[derive(Resource)]
+struct ShadowSettings {
+    point_size: usize,
+    directional_size: usize,
+    cascades: usize,
+    // Before the world scale is applied.
+    max_distance: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        // Bevy's defaults
+        let cascades = CascadeShadowConfigBuilder::default();
+        Self {
+            point_size: PointLightShadowMap::default().size,
+            directional_size: DirectionalLightShadowMap::default().size,
+            cascades: cascades.num_cascades,
+            max_distance: cascades.maximum_distance,
+        }
+    }
+}
+
+// Returns the entry after `current` in `values`, wrapping around. Values not in the list start
+// over at the first one.
+fn next_in<T: PartialEq + Copy>(values: &[T], current: T) -> T {
+    let index = values.iter().position(|value| *value == current);
+    values[index.map_or(0, |index| (index + 1) % values.len())]
+}
+
+#[derive(Component)]
+struct ShadowSettingsText;
+
+fn spawn_shadow_settings_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((ShadowSettingsText, Text::new("")));
+}
+
+fn cycle_shadow_settings(
+    mut settings: ResMut<ShadowSettings>,
+    directional_query: Query<(), (With<ShadowLight>, With<DirectionalLight>)>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    if input.just_pressed(KeyCode::Slash) {
+        // Keep the point and directional maps the same distance apart as Bevy's defaults
+        settings.directional_size = next_in(&SHADOW_MAP_SIZES, settings.directional_size);
+        let point_size = settings.directional_size / 2;
+        settings.point_size = if point_size > MAX_POINT_SHADOW_MAP_SIZE {
+            warn!(
+                "Point light shadow maps are capped at {MAX_POINT_SHADOW_MAP_SIZE}, not {point_size}"
+            );
+            MAX_POINT_SHADOW_MAP_SIZE
+        } else {
+            point_size.max(SHADOW_MAP_SIZES[0])
+        };
+    }
+
+    let cascades = input.just_pressed(KeyCode::F11);
+    let distance = input.just_pressed(KeyCode::F12);
+    if (cascades || distance) && directional_query.is_empty() {
+        warn!("There is no directional light to change the shadow cascades of");
+        return;
+    }
+    if cascades {
+        settings.cascades = next_in(&SHADOW_CASCADE_COUNTS, settings.cascades);
+    }
+    if distance {
+        settings.max_distance = next_in(&SHADOW_DISTANCES, settings.max_distance);
+    }
+}
+
+fn apply_shadow_settings(
+    settings: Res<ShadowSettings>,
+    mut point_shadow_map: ResMut<PointLightShadowMap>,
+    mut directional_shadow_map: ResMut<DirectionalLightShadowMap>,
+    mut cascade_query: Query<&mut CascadeShadowConfig, With<ShadowLight>>,
+    world_scale: Res<WorldScale>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    point_shadow_map.size = settings.point_size;
+    directional_shadow_map.size = settings.directional_size;
+    for mut cascade_config in &mut cascade_query {
+        *cascade_config = CascadeShadowConfigBuilder {
+            num_cascades: settings.cascades,
+            maximum_distance: settings.max_distance * world_scale.0,
+            first_cascade_far_bound: 10.0 * world_scale.0,
+            minimum_distance: 0.1 * world_scale.0,
+            ..default()
+        }
+        .build();
+    }
+}
+
+fn update_shadow_settings_text(
+    mut text: Single<&mut Text, With<ShadowSettingsText>>,
+    settings: Res<ShadowSettings>,
+) {
+    if settings.is_changed() {
+        text.0 = format!(
+            "Shadow maps: point {}, directional {}, {} cascades to {:.0} m",
+            settings.point_size,
+            settings.directional_size,
+            settings.cascades,
+            settings.max_distance
+        );
+    }
+}
+
+// Plugin that softens the shadows of the main light with percentage-closer soft shadows, toggled
+// with Insert. Home and End change how soft they get.
+struct SoftShadowsPlugin;
+impl Plugin for SoftShadowsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoftShadows>()
+            .add_systems(PostStartup, spawn_soft_shadows_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (
+                    adjust_soft_shadows,
+                    apply_soft_shadows,
+                    update_soft_shadows_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct SoftShadows {
+    enabled: bool,
+    // Radius of the light, in meters, which sets how wide the penumbra gets.
+    softness: f32,
+    // Change per second while a key is held.
+    adjust_speed: f32,
+}
+
+impl Default for SoftShadows {
+    fn default() -> Self {
+        // Hard shadows, like a default PointLight
+        Self {
+            enabled: false,
+            softness: 0.5,
+            adjust_speed: 0.5,
+        }
+    }
+}
+
+#[derive(Component)]
+struct SoftShadowsText;
+
+fn spawn_soft_shadows_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((SoftShadowsText, Text::new("")));
+}
+
+fn adjust_soft_shadows(
+    mut soft_shadows: ResMut<SoftShadows>,
+    input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+) {
+    // With Shift these keys control the height fog
+    if input.pressed(KeyCode::ShiftLeft) {
+        return;
+    }
+    if input.just_pressed(KeyCode::Insert) {
+        soft_shadows.enabled = !soft_shadows.enabled;
+    }
+
+    let step = soft_shadows.adjust_speed * time.delta_secs();
+    let change = match (input.pressed(KeyCode::Home), input.pressed(KeyCode::End)) {
+        (true, false) => -step,
+        (false, true) => step,
+        _ => 0.0,
+    };
+    if change != 0.0 {
+        soft_shadows.softness = (soft_shadows.softness + change).clamp(0.01, 5.0);
+    }
+}
+
+fn apply_soft_shadows(
+    soft_shadows: Res<SoftShadows>,
+    mut light_query: Query<&mut PointLight, With<ShadowLight>>,
+    world_scale: Res<WorldScale>,
+) {
+    if !soft_shadows.is_changed() {
+        return;
+    }
+
+    for mut light in &mut light_query {
+        light.soft_shadows_enabled = soft_shadows.enabled;
+        // The radius also widens specular highlights, so it goes back to a point light when off
+        light.radius = if soft_shadows.enabled {
+            soft_shadows.softness * world_scale.0
+        } else {
+            0.0
+        };
+    }
+}
+
+fn update_soft_shadows_text(
+    mut text: Single<&mut Text, With<SoftShadowsText>>,
+    soft_shadows: Res<SoftShadows>,
+) {
+    if soft_shadows.is_changed() {
+        text.0 = if soft_shadows.enabled {
+            format!("Soft shadows: {:.02} m", soft_shadows.softness)
+        } else {
+            "Soft shadows: false".to_string()
+        };
+    }
+}
+
+// Plugin that swaps the scene lights for another preset with Shift+F8. The lights of a preset
+// are tagged, so switching removes the previous set before spawning the next one.
+struct LightingPresetsPlugin;
+impl Plugin for LightingPresetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, spawn_lighting_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (
+                    cycle_lighting_preset,
+                    apply_lighting_preset,
+                    reapply_light_settings,
+                    update_lighting_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+// One light of a lighting preset.
+#[derive(Clone, Copy, Debug)]
+struct LightSpec {
+    color: Color,
+    shadows: bool,
+    source: LightSource,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum LightSource {
+    // Position in meters, luminous power in lumens.
+    Point { position: Vec3, intensity: f32 },
+    // Direction the light shines in, illuminance in lux.
+    Directional { direction: Vec3, illuminance: f32 },
+}
+
+#[derive(Clone, Debug)]
+struct LightingPreset {
+    name: &'static str,
+    lights: Vec<LightSpec>,
+}
+
+// The lighting presets, and which one is in the scene. The first one is spawned on startup.
+#[derive(Resource)]
+struct LightingPresets {
+    presets: Vec<LightingPreset>,
+    active: usize,
+}
+
+impl Default for LightingPresets {
+    fn default() -> Self {
+        let point = |position, intensity, color: Srgba, shadows| LightSpec {
+            color: color.into(),
+            shadows,
+            source: LightSource::Point {
+                position,
+                intensity,
+            },
+        };
+        let directional = |direction, illuminance, color: Srgba, shadows| LightSpec {
+            color: color.into(),
+            shadows,
+            source: LightSource::Directional {
+                direction,
+                illuminance,
+            },
+        };
+
+        Self {
+            presets: vec![
+                LightingPreset {
+                    name: "overhead",
+                    lights: vec![point(
+                        Vec3::new(0.0, 45.0, 0.0),
+                        PointLight::default().intensity,
+                        tailwind::NEUTRAL_300,
+                        true,
+                    )],
+                },
+                LightingPreset {
+                    name: "noon",
+                    lights: vec![directional(
+                        Vec3::new(0.3, -1.0, 0.2),
+                        light_consts::lux::AMBIENT_DAYLIGHT,
+                        tailwind::AMBER_50,
+                        true,
+                    )],
+                },
+                LightingPreset {
+                    name: "torchlit",
+                    lights: vec![
+                        point(
+                            Vec3::new(2.0, 2.5, 5.0),
+                            80_000.0,
+                            tailwind::ORANGE_400,
+                            true,
+                        ),
+                        point(
+                            Vec3::new(2.0, 2.5, 20.0),
+                            80_000.0,
+                            tailwind::ORANGE_400,
+                            false,
+                        ),
+                        point(
+                            Vec3::new(12.0, 2.5, 23.5),
+                            80_000.0,
+                            tailwind::AMBER_400,
+                            false,
+                        ),
+                        point(
+                            Vec3::new(17.0, 2.5, 11.5),
+                            80_000.0,
+                            tailwind::ORANGE_400,
+                            false,
+                        ),
+                    ],
+                },
+                LightingPreset {
+                    name: "moody",
+                    lights: vec![
+                        directional(
+                            Vec3::new(-0.4, -1.0, -0.3),
+                            light_consts::lux::OVERCAST_DAY / 4.0,
+                            tailwind::INDIGO_300,
+                            true,
+                        ),
+                        point(
+                            Vec3::new(5.0, 1.0, 33.0),
+                            40_000.0,
+                            tailwind::RED_500,
+                            false,
+                        ),
+                    ],
+                },
+            ],
+            active: 0,
+        }
+    }
+}
+
+// Marks the lights spawned for the active lighting preset.
+#[derive(Component)]
+struct PresetLight;
+
+fn spawn_preset_lights(commands: &mut Commands, preset: &LightingPreset, world_scale: f32) {
+    for light in &preset.lights {
+        let mut entity = commands.spawn(PresetLight);
+        match light.source {
+            LightSource::Point {
+                position,
+                intensity,
+            } => {
+                entity.insert((
+                    PointLight {
+                        color: light.color,
+                        intensity,
+                        shadows_enabled: light.shadows,
+                        range: PointLight::default().range * world_scale,
+                        ..default()
+                    },
+                    Transform::from_translation(position * world_scale),
+                ));
+            }
+            LightSource::Directional {
+                direction,
+                illuminance,
+            } => {
+                entity.insert((
+                    DirectionalLight {
+                        color: light.color,
+                        illuminance,
+                        shadows_enabled: light.shadows,
+                        ..default()
+                    },
+                    Transform::default().looking_to(direction, Vec3::Y),
+                ));
+            }
+        }
+        // The shadow settings and soft shadows apply to the lights that cast shadows
+        if light.shadows {
+            entity.insert(ShadowLight);
+        }
+    }
+}
+
+#[derive(Component)]
+struct LightingText;
+
+fn spawn_lighting_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((LightingText, Text::new("")));
+}
+
+fn cycle_lighting_preset(mut presets: ResMut<LightingPresets>, input: Res<ButtonInput<KeyCode>>) {
+    if input.just_pressed(KeyCode::F8) && input.pressed(KeyCode::ShiftLeft) {
+        presets.active = (presets.active + 1) % presets.presets.len();
+    }
+}
+
+fn apply_lighting_preset(
+    mut commands: Commands,
+    presets: Res<LightingPresets>,
+    light_query: Query<Entity, With<PresetLight>>,
+    world_scale: Res<WorldScale>,
+) {
+    // The first preset is spawned along with the scene
+    if !presets.is_changed() || presets.is_added() {
+        return;
+    }
+
+    for entity in &light_query {
+        commands.entity(entity).despawn();
+    }
+    spawn_preset_lights(
+        &mut commands,
+        &presets.presets[presets.active],
+        world_scale.0,
+    );
+}
+
+// Lights from a new preset start out with default shadows, this makes the shadow settings and
+// soft shadows apply to them as well.
+fn reapply_light_settings(
+    mut shadow_settings: ResMut<ShadowSettings>,
+    mut soft_shadows: ResMut<SoftShadows>,
+    added_query: Query<(), Added<ShadowLight>>,
+) {
+    if !added_query.is_empty() {
+        shadow_settings.set_changed();
+        soft_shadows.set_changed();
+    }
+}
+
+fn update_lighting_text(
+    mut text: Single<&mut Text, With<LightingText>>,
+    presets: Res<LightingPresets>,
+) {
+    if presets.is_changed() {
+        text.0 = format!(
+            "Lighting: {} ({}/{})",
+            presets.presets[presets.active].name,
+            presets.active + 1,
+            presets.presets.len()
+        );
+    }
+}
+
+// Plugin for a ground fog that is thickest at the floor and thins out towards its ceiling,
+// coloured like the floor's glow. Shift+Insert turns it on and off, Shift+Home/End change how
+// dense it is.
+struct HeightFogPlugin;
+impl Plugin for HeightFogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HeightFog>()
+            .add_systems(Startup, create_height_fog_texture)
+            .add_systems(PostStartup, spawn_height_fog_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (adjust_height_fog, apply_height_fog, update_height_fog_text).chain(),
+            );
+    }
+}
+
+// Density at the fog ceiling relative to the floor is e^-HEIGHT_FOG_FALLOFF.
+const HEIGHT_FOG_FALLOFF: f32 = 4.0;
+// Rows in the density texture, from the floor to the ceiling.
+const HEIGHT_FOG_RESOLUTION: usize = 32;
+// Width and depth of the fog volume, in meters. Large enough to cover the whole scene.
+const HEIGHT_FOG_EXTENT: f32 = 200.0;
+
+#[derive(Resource)]
+struct HeightFog {
+    enabled: bool,
+    color: Color,
+    // Height of the densest part of the fog, in meters.
+    base_height: f32,
+    // How far above the base the fog reaches, in meters. Nothing above it is fogged, so the
+    // sky stays clear.
+    thickness: f32,
+    // Density at the base height.
+    density: f32,
+    // Relative change per second while a key is held.
+    adjust_speed: f32,
+}
+
+impl Default for HeightFog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color: FLOOR_EMISSIVE.into(),
+            base_height: 0.0,
+            thickness: 4.0,
+            density: 0.3,
+            adjust_speed: 1.0,
+        }
+    }
+}
+
+// Density falloff from the floor up, as a 3D texture one texel wide and deep.
+#[derive(Resource)]
+struct HeightFogTexture(Handle<Image>);
+
+// Marks the fog volume spawned for the height fog.
+#[derive(Component)]
+struct HeightFogVolume;
+
+#[derive(Component)]
+struct HeightFogText;
+
+fn create_height_fog_texture(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let data = (0..HEIGHT_FOG_RESOLUTION)
+        .map(|row| {
+            let height = row as f32 / (HEIGHT_FOG_RESOLUTION - 1) as f32;
+            ((-HEIGHT_FOG_FALLOFF * height).exp() * 255.0).round() as u8
+        })
+        .collect();
+    let mut image = Image::new(
+        Extent3d {
+            width: 1,
+            height: HEIGHT_FOG_RESOLUTION as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D3,
+        data,
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    // Clamp rather than the app's default repeat, or the ceiling would blend with the floor
+    image.sampler = ImageSampler::linear();
+    commands.insert_resource(HeightFogTexture(images.add(image)));
+}
+
+fn spawn_height_fog_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((HeightFogText, Text::new("")));
+}
+
+fn adjust_height_fog(
+    mut fog: ResMut<HeightFog>,
+    input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+) {
+    // Without Shift these keys control the soft shadows
+    if !input.pressed(KeyCode::ShiftLeft) {
+        return;
+    }
+    if input.just_pressed(KeyCode::Insert) {
+        fog.enabled = !fog.enabled;
+    }
+
+    let factor = (fog.adjust_speed * time.delta_secs()).exp();
+    match (input.pressed(KeyCode::Home), input.pressed(KeyCode::End)) {
+        (true, false) => fog.density = (fog.density / factor).max(0.01),
+        (false, true) => fog.density = (fog.density * factor).min(10.0),
+        _ => {}
+    }
+}
+
+fn apply_height_fog(
+    mut commands: Commands,
+    fog: Res<HeightFog>,
+    texture: Res<HeightFogTexture>,
+    camera: Single<Entity, With<FreeCamera>>,
+    volume_query: Query<Entity, With<HeightFogVolume>>,
+    world_scale: Res<WorldScale>,
+) {
+    if !fog.is_changed() {
+        return;
+    }
+
+    for entity in &volume_query {
+        commands.entity(entity).despawn();
+    }
+    if !fog.enabled {
+        commands.entity(*camera).remove::<VolumetricFog>();
+        return;
+    }
+
+    commands.entity(*camera).insert(VolumetricFog {
+        // Lit by the ambient term only, the scene lights don't need to be volumetric
+        ambient_color: Color::WHITE,
+        ambient_intensity: 1.0,
+        ..default()
+    });
+    // The fog volume is a unit cube, centered on the middle of the fog layer
+    let scale = world_scale.0;
+    commands.spawn((
+        HeightFogVolume,
+        FogVolume {
+            fog_color: fog.color,
+            density_factor: fog.density / scale,
+            density_texture: Some(texture.0.clone()),
+            ..default()
+        },
+        Transform::from_xyz(0.0, (fog.base_height + fog.thickness / 2.0) * scale, 0.0)
+            .with_scale(Vec3::new(HEIGHT_FOG_EXTENT, fog.thickness, HEIGHT_FOG_EXTENT) * scale),
+    ));
+}
+
+fn update_height_fog_text(mut text: Single<&mut Text, With<HeightFogText>>, fog: Res<HeightFog>) {
+    if fog.is_changed() {
+        text.0 = format!("Height fog: {} (density {:.02})", fog.enabled, fog.density);
+    }
+}
+
+// Plugin that tunes the metallic and roughness of the walls under the point light
+struct WallSurfacePlugin;
+impl Plugin for WallSurfacePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WallSurface>()
+            .add_systems(PostStartup, spawn_wall_surface_text.after(spawn_text))
+            .add_systems(
+                Update,
+                (
+                    adjust_wall_surface,
+                    apply_wall_surface,
+                    update_wall_surface_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+// Surface parameters applied to every wall material, both kept within [0, 1].
+#[derive(Resource)]
+struct WallSurface {
+    metallic: f32,
+    roughness: f32,
+    // Change per second while a key is held.
+    adjust_speed: f32,
+}
+
+impl Default for WallSurface {
+    fn default() -> Self {
+        // Same as a default StandardMaterial
+        Self {
+            metallic: 0.0,
+            roughness: 0.5,
+            adjust_speed: 0.5,
+        }
+    }
+}
+
+#[derive(Component)]
+struct WallSurfaceText;
+
+fn spawn_wall_surface_text(mut commands: Commands, panel: Single<Entity, With<InfoPanel>>) {
+    commands
+        .entity(*panel)
+        .with_child((WallSurfaceText, Text::new("")));
+}
+
+fn adjust_wall_surface(
+    mut surface: ResMut<WallSurface>,
+    input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+) {
+    let step = surface.adjust_speed * time.delta_secs();
+    let axis = |decrease, increase| match (input.pressed(decrease), input.pressed(increase)) {
+        (true, false) => -step,
+        (false, true) => step,
+        _ => 0.0,
+    };
+    let metallic = axis(KeyCode::Digit5, KeyCode::Digit6);
+    let roughness = axis(KeyCode::Digit7, KeyCode::Digit8);
+
+    if metallic != 0.0 {
+        surface.metallic = (surface.metallic + metallic).clamp(0.0, 1.0);
+    }
+    if roughness != 0.0 {
+        surface.roughness = (surface.roughness + roughness).clamp(0.0, 1.0);
+    }
+}
+
+fn apply_wall_surface(
+    surface: Res<WallSurface>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    wall_query: Query<&MeshMaterial3d<StandardMaterial>, With<WallMaterial>>,
+) {
+    if !surface.is_changed() {
+        return;
+    }
+
+    let ids: HashSet<_> = wall_query.iter().map(|material| material.id()).collect();
+    for id in ids {
+        if let Some(material) = materials.get_mut(id) {
+            material.metallic = surface.metallic;
+            material.perceptual_roughness = surface.roughness;
+        }
+    }
+}
+
+fn update_wall_surface_text(
+    mut text: Single<&mut Text, With<WallSurfaceText>>,
+    surface: Res<WallSurface>,
+) {
+    if surface.is_changed() {
+        text.0 = format!(
+            "Walls: metallic {:.02}, roughness {:.02}",
+            surface.metallic, surface.roughness
+        );
+    }
+}
+
+// Plugin that fires a message whenever the camera walks into a trigger volume
+struct TriggerPlugin;
+impl Plugin for TriggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<TriggerFired>()
+            .add_systems(Update, (detect_triggers, announce_triggers).chain());
+    }
+}
+
+#[derive(Message, Debug, Clone)]
+struct TriggerFired {
+    entity: Entity,
+    payload: String,
+}
+
+fn detect_triggers(
+    camera: Single<&GlobalTransform, With<FreeCamera>>,
+    mut trigger_query: Query<(Entity, &mut Trigger, &Aabb, &GlobalTransform)>,
+    mut fired: MessageWriter<TriggerFired>,
+) {
+    let eye = camera.translation();
+    for (entity, mut trigger, aabb, global_transform) in &mut trigger_query {
+        // Same world space box the camera collision uses
+        let affine = global_transform.affine();
+        let center = Vec3::from(affine.transform_point3a(aabb.center));
+        let half_extents = Vec3::from(affine.matrix3.abs() * aabb.half_extents);
+        let inside = (eye - center).abs().cmple(half_extents).all();
+
+        if inside && !trigger.inside {
+            fired.write(TriggerFired {
+                entity,
+                payload: trigger.payload.clone(),
+            });
+        }
+        trigger.inside = inside;
+    }
+}
+
+fn announce_triggers(mut fired: MessageReader<TriggerFired>) {
+    for TriggerFired { entity, payload } in fired.read() {
+        info!("Trigger {entity} fired: {payload}");
+    }
+}
+
+// Plugin that plays footstep sounds while walking. The sound is not shipped with the demo,
+// put a clip at `assets/sounds/footstep.ogg` to hear it.
+struct FootstepsPlugin;
+impl Plugin for FootstepsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, add_footsteps.after(spawn_camera))
+            .add_systems(Update, play_footsteps);
+    }
+}
+
+#[derive(Component)]
+struct Footsteps {
+    sound: Handle<AudioSource>,
+    // Horizontal distance covered between two steps.
+    stride: f32,
+    // Shortest time between two steps, so very fast movement doesn't turn into a drone.
+    min_interval: f32,
+    // Distance covered since the last step.
+    distance: f32,
+    // Time since the last step.
+    since_last: f32,
+}
+
+impl Footsteps {
+    fn new(sound: Handle<AudioSource>, stride: f32) -> Self {
+        Self {
+            sound,
+            stride,
+            min_interval: 0.2,
+            distance: 0.0,
+            since_last: 0.0,
+        }
+    }
+}
+
+fn add_footsteps(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    world_scale: Res<WorldScale>,
+    camera: Single<Entity, With<FreeCamera>>,
+) {
+    commands.entity(*camera).insert(Footsteps::new(
+        asset_server.load("sounds/footstep.ogg"),
+        0.75 * world_scale.0,
+    ));
+}
+
+fn play_footsteps(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut camera_query: Query<(&mut Footsteps, &FreeCamera, &FreeCameraState)>,
+) {
+    for (mut footsteps, free_camera, free_camera_state) in &mut camera_query {
+        footsteps.since_last += time.delta_secs();
+
+        // Velocity is stored along the movement axes, x and z are the horizontal ones
+        let speed = free_camera_state.velocity.xz().length();
+        let walking = free_camera.movement_mode == MovementMode::Walk
+            && free_camera_state.grounded
+            && speed > 0.1;
+        if !walking || footsteps.stride <= 0.0 {
+            footsteps.distance = 0.0;
+            continue;
+        }
+
+        footsteps.distance += speed * time.delta_secs();
+        if footsteps.distance >= footsteps.stride && footsteps.since_last >= footsteps.min_interval
+        {
+            // Drop any extra strides instead of catching up on them
+            footsteps.distance %= footsteps.stride;
+            footsteps.since_last = 0.0;
+            commands.spawn((
+                AudioPlayer::new(footsteps.sound.clone()),
+                PlaybackSettings::DESPAWN,
+            ));
+        }
+    }
+}
+
+// Plugin that makes looping ambient sounds louder as the camera gets closer to them. Like the
+// footsteps, the clips are not shipped with the demo.
+struct AmbientSoundPlugin;
+impl Plugin for AmbientSoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, add_listener.after(spawn_camera))
+            .add_systems(Update, update_ambient_sounds);
+    }
+}
+
+// The camera hears the spatial sounds, its transform is the listener position.
+fn add_listener(mut commands: Commands, camera: Single<Entity, With<FreeCamera>>) {
+    commands.entity(*camera).insert(SpatialListener::default());
+}
+
+fn update_ambient_sounds(
+    listener: Single<&GlobalTransform, With<SpatialListener>>,
+    mut sound_query: Query<(&AmbientSound, &GlobalTransform, &mut SpatialAudioSink)>,
+) {
+    let position = listener.translation();
+    let gains: Vec<f32> = sound_query
+        .iter()
+        .map(|(sound, global_transform, _)| {
+            sound.gain(position.distance(global_transform.translation()))
+        })
+        .collect();
+
+    // Scale everything down together when the sounds would add up past full volume
+    let total: f32 = gains.iter().sum();
+    let headroom = if total > 1.0 { total.recip() } else { 1.0 };
+
+    for ((_, _, mut sink), gain) in sound_query.iter_mut().zip(gains) {
+        sink.set_volume(Volume::Linear(gain * headroom));
+    }
+}
+
+// Plugin that draws the bounds of every `CameraCollider`, toggled with F1. Handy for finding out
+// why the camera got stuck.
+struct ColliderDebugPlugin;
+impl Plugin for ColliderDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ColliderDebug>().add_systems(
+            Update,
+            (toggle_collider_debug, draw_collider_bounds).chain(),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+struct ColliderDebug {
+    enabled: bool,
+}
+
+// Colliders within this many collision radii of the camera are drawn as nearby.
+const COLLIDER_NEAR_RADII: f32 = 4.0;
+
+fn toggle_collider_debug(mut debug: ResMut<ColliderDebug>, input: Res<ButtonInput<KeyCode>>) {
+    // Shift+F1 toggles the basis gizmo instead
+    if input.just_pressed(KeyCode::F1) && !input.pressed(KeyCode::ShiftLeft) {
+        debug.enabled = !debug.enabled;
+    }
+}
+
+fn draw_collider_bounds(
+    debug: Res<ColliderDebug>,
+    camera: Single<(&GlobalTransform, &FreeCamera, &FreeCameraState)>,
+    collider_query: Query<(Entity, &Aabb, &GlobalTransform), With<CameraCollider>>,
+    mut gizmos: Gizmos,
+) {
+    // Gizmos only last a frame, so not drawing them is enough to hide the boxes
+    if !debug.enabled {
+        return;
+    }
+
+    let (camera_transform, free_camera, state) = *camera;
+    let eye = camera_transform.translation_vec3a();
+    let near_distance = free_camera.collision_radius * COLLIDER_NEAR_RADII;
+
+    for (entity, aabb, global_transform) in &collider_query {
+        let bounds = collider_bounds(aabb, global_transform);
+        let color = if state.contacts.contains(&entity) {
+            tailwind::RED_500
+        } else if bounds.closest_point(eye).distance(eye) <= near_distance {
+            tailwind::AMBER_400
+        } else {
+            tailwind::SKY_700
+        };
+        gizmos.aabb_3d(bounds, Transform::IDENTITY, color);
+    }
+}
+
+// Plugin that draws the camera's right, up and forward vectors as red, green and blue arrows a
+// little in front of it, toggled with Shift+F1, for checking the look math.
+struct BasisGizmoPlugin;
+impl Plugin for BasisGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BasisGizmo>()
+            .add_systems(Update, (toggle_basis_gizmo, draw_basis_gizmo).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+struct BasisGizmo {
+    enabled: bool,
+}
+
+// How far in front of the camera the arrows start, and how long they are, in meters.
+const BASIS_GIZMO_DISTANCE: f32 = 1.0;
+const BASIS_GIZMO_LENGTH: f32 = 0.15;
+
+fn toggle_basis_gizmo(mut gizmo: ResMut<BasisGizmo>, input: Res<ButtonInput<KeyCode>>) {
+    if input.just_pressed(KeyCode::F1) && input.pressed(KeyCode::ShiftLeft) {
+        gizmo.enabled = !gizmo.enabled;
+    }
+}
+
+fn draw_basis_gizmo(
+    gizmo: Res<BasisGizmo>,
+    camera: Single<(&GlobalTransform, &FreeCamera, &FreeCameraState)>,
+    world_scale: Res<WorldScale>,
+    mut gizmos: Gizmos,
+) {
+    if !gizmo.enabled {
+        return;
+    }
+
+    let (camera_transform, free_camera, state) = *camera;
+    let forward = state.forward(free_camera);
+    let origin = camera_transform.translation() + forward * BASIS_GIZMO_DISTANCE * world_scale.0;
+    let length = BASIS_GIZMO_LENGTH * world_scale.0;
+    for (direction, color) in [
+        (state.right(free_camera), tailwind::RED_500),
+        (state.up(free_camera), tailwind::GREEN_500),
+        (forward, tailwind::BLUE_500),
+    ] {
+        gizmos.arrow(origin, origin + direction * length, color);
+    }
+}
+
+// Plugin that adds a map of the explored parts of the world to the bottom right corner. Cells the
+// camera has passed through are revealed and the rest stay dark. Shift+F2 shows or hides the map
+// and Shift+F3 forgets what has been explored.
+struct ExplorationMapPlugin;
+impl Plugin for ExplorationMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Exploration>().add_systems(
+            Update,
+            (
+                compute_world_bounds.run_if(not(resource_exists::<WorldBounds>)),
+                spawn_exploration_map.run_if(resource_added::<WorldBounds>),
+                (
+                    control_exploration_map,
+                    explore_cells,
+                    update_exploration_map,
+                )
+                    .chain()
+                    .run_if(resource_exists::<WorldBounds>),
+            )
+                .chain(),
+        );
+    }
+}
+
+// Side of an exploration cell, in meters before the world scale.
+const EXPLORATION_CELL_SIZE: f32 = 2.0;
+// Side of a cell on the map, in pixels.
+const EXPLORATION_MAP_CELL_PX: f32 = 4.0;
+
+// Area covered by the colliders on the XZ plane, split into the exploration cells.
+#[derive(Resource, Clone, Copy, Debug)]
+struct WorldBounds {
+    min: Vec2,
+    max: Vec2,
+    cell_size: f32,
+    // Number of cells along X and Z.
+    cells: UVec2,
+}
+
+impl WorldBounds {
+    fn new(min: Vec2, max: Vec2, cell_size: f32) -> Self {
+        let cells = ((max - min) / cell_size).ceil().as_uvec2().max(UVec2::ONE);
+        Self {
+            min,
+            max,
+            cell_size,
+            cells,
+        }
+    }
+
+    // The cell containing `position`, if it lies within the bounds.
+    fn cell(&self, position: Vec3) -> Option<UVec2> {
+        let position = position.xz();
+        if position.cmplt(self.min).any() || position.cmpgt(self.max).any() {
+            return None;
+        }
+        let cell = ((position - self.min) / self.cell_size).as_uvec2();
+        Some(cell.min(self.cells - UVec2::ONE))
+    }
+}
+
+#[derive(Resource)]
+struct Exploration {
+    visible: bool,
+    visited: HashSet<UVec2>,
+    // Cell the camera is in, if it's within the world bounds.
+    current: Option<UVec2>,
+}
+
+impl Default for Exploration {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            visited: HashSet::new(),
+            current: None,
+        }
+    }
+}
+
+#[derive(Component)]
+struct ExplorationMap;
+
+#[derive(Component)]
+struct ExplorationCell(UVec2);
+
+// Waits for the colliders to get their bounds, which happens after the first frame is rendered.
+fn compute_world_bounds(
+    mut commands: Commands,
+    collider_query: Query<(&Aabb, &GlobalTransform), With<CameraCollider>>,
+    world_scale: Res<WorldScale>,
+) {
+    let bounds = collider_query
+        .iter()
+        .map(|(aabb, global_transform)| collider_bounds(aabb, global_transform))
+        .map(|bounds| (bounds.min.xz(), bounds.max.xz()))
+        .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)));
+    let Some((min, max)) = bounds else {
+        return;
+    };
+    commands.insert_resource(WorldBounds::new(
+        min,
+        max,
+        EXPLORATION_CELL_SIZE * world_scale.0,
+    ));
+}
+
+fn spawn_exploration_map(mut commands: Commands, bounds: Res<WorldBounds>) {
+    commands
+        .spawn((
+            ExplorationMap,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: px(12),
+                right: px(12),
+                display: Display::Grid,
+                grid_template_columns: RepeatedGridTrack::px(
+                    bounds.cells.x as usize,
+                    EXPLORATION_MAP_CELL_PX,
+                ),
+                grid_auto_rows: vec![GridTrack::px(EXPLORATION_MAP_CELL_PX)],
+                ..default()
+            },
+        ))
+        .with_children(|map| {
+            // Rows run along Z, so the map shows the world from above with -Z at the top
+            for z in 0..bounds.cells.y {
+                for x in 0..bounds.cells.x {
+                    map.spawn((ExplorationCell(UVec2::new(x, z)), Node::default()));
+                }
+            }
+        });
+}
+
+fn control_exploration_map(mut exploration: ResMut<Exploration>, input: Res<ButtonInput<KeyCode>>) {
+    if !input.pressed(KeyCode::ShiftLeft) {
+        return;
+    }
+    if input.just_pressed(KeyCode::F2) {
+        exploration.visible = !exploration.visible;
+    }
+    if input.just_pressed(KeyCode::F3) {
+        exploration.visited.clear();
+    }
+}
+
+fn explore_cells(
+    mut exploration: ResMut<Exploration>,
+    camera: Single<&GlobalTransform, With<FreeCamera>>,
+    bounds: Res<WorldBounds>,
+) {
+    let current = bounds.cell(camera.translation());
+    if exploration.current != current {
+        exploration.current = current;
+    }
+    if let Some(cell) = current
+        && !exploration.visited.contains(&cell)
+    {
+        exploration.visited.insert(cell);
+    }
+}
+
+fn update_exploration_map(
+    exploration: Res<Exploration>,
+    mut map_query: Query<&mut Visibility, With<ExplorationMap>>,
+    mut cell_query: Query<(&ExplorationCell, &mut BackgroundColor)>,
+) {
+    if !exploration.is_changed() {
+        return;
+    }
+
+    for mut visibility in &mut map_query {
+        *visibility = if exploration.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    for (cell, mut background) in &mut cell_query {
+        background.0 = if exploration.current == Some(cell.0) {
+            Color::from(tailwind::AMBER_400)
+        } else if exploration.visited.contains(&cell.0) {
+            Color::from(tailwind::STONE_300).with_alpha(0.8)
+        } else {
+            Color::BLACK.with_alpha(0.6)
+        };
+    }
+}
+
+// Plugin that adds a fill light riding along with the camera, toggled with F8. It sits over the
+// shoulder rather than on the view axis, so dark objects get some shape instead of a flat
+// flashlight look.
+struct FollowLightPlugin;
+impl Plugin for FollowLightPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_follow_light)
+            .add_systems(Update, (toggle_follow_light, follow_camera_light).chain());
+    }
+}
+
+// Keeps a light at a fixed offset from the camera.
+#[derive(Component)]
+struct FollowLight {
+    // Position relative to the camera, in camera-local space.
+    offset: Vec3,
+    // Luminous power of the light, in lumens.
+    intensity: f32,
+}
+
+fn spawn_follow_light(mut commands: Commands, world_scale: Res<WorldScale>) {
+    commands.spawn((
+        FollowLight {
+            offset: Vec3::new(0.6, 0.4, 0.3) * world_scale.0,
+            intensity: 200_000.0,
+        },
+        PointLight {
+            color: Color::from(tailwind::AMBER_100),
+            range: 15.0 * world_scale.0,
             ..default()
         },
+        Visibility::Hidden,
     ));
 }
+
+fn toggle_follow_light(
+    mut light_query: Query<&mut Visibility, With<FollowLight>>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    // Shift+F8 cycles the lighting presets instead
+    if !input.just_pressed(KeyCode::F8) || input.pressed(KeyCode::ShiftLeft) {
+        return;
+    }
+    for mut visibility in &mut light_query {
+        visibility.toggle_inherited_hidden();
+    }
+}
+
+fn follow_camera_light(
+    camera: Single<&Transform, With<FreeCamera>>,
+    mut light_query: Query<(&FollowLight, &mut PointLight, &mut Transform), Without<FreeCamera>>,
+) {
+    for (follow_light, mut light, mut transform) in &mut light_query {
+        *transform = Transform::from_translation(camera.transform_point(follow_light.offset))
+            .with_rotation(camera.rotation);
+        light.intensity = follow_light.intensity;
+    }
+}
+
+// Plugin that spawns the scene and lighting.
+struct ScenePlugin;
+impl Plugin for ScenePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TextureRoot>()
+            .init_resource::<AssetCache>()
+            .init_resource::<StartupScene>()
+            .init_resource::<LightingPresets>()
+            .add_systems(
+                Startup,
+                (
+                    check_texture_root,
+                    spawn_lights,
+                    (
+                        load_startup_scene,
+                        (
+                            spawn_world.run_if(|scene: Res<StartupScene>| scene.0.is_none()),
+                            spawn_scene_description,
+                        ),
+                    )
+                        .chain(),
+                ),
+            )
+            .add_systems(Update, (apply_loaded_textures, export_scene_description));
+    }
+}
+
+fn spawn_lights(
+    mut commands: Commands,
+    presets: Res<LightingPresets>,
+    world_scale: Res<WorldScale>,
+) {
+    spawn_preset_lights(
+        &mut commands,
+        &presets.presets[presets.active],
+        world_scale.0,
+    );
+}
+
+// File with extra objects to place in the built-in world, skipped if it doesn't exist.
+const SCENE_DESCRIPTION_PATH: &str = "scene.ron";
+
+// Scene description passed as the first command line argument, e.g.
+// `cargo run -- levels/atrium.ron`, which replaces the built-in world. `None` without an
+// argument, or when the file couldn't be loaded.
+#[derive(Resource, Default)]
+struct StartupScene(Option<(PathBuf, SceneDescription)>);
+
+// Shape of a scene object, with the same parameters as the primitives `spawn_world` uses.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+enum MeshKind {
+    // Box with the given full lengths.
+    Cuboid { x: f32, y: f32, z: f32 },
+    Sphere { r: f32 },
+    // Upright cylinder with radius `r` and full height `h`.
+    Cylinder { r: f32, h: f32 },
+    // Plane facing `normal`, `size` is its half size.
+    Plane { normal: Vec3, size: Vec2 },
+    // Disc facing +Z.
+    Circle { r: f32 },
+}
+
+impl MeshKind {
+    fn mesh(&self) -> Mesh {
+        match *self {
+            MeshKind::Cuboid { x, y, z } => Cuboid::new(x, y, z).into(),
+            MeshKind::Sphere { r } => Sphere::new(r).into(),
+            MeshKind::Cylinder { r, h } => Cylinder::new(r, h).into(),
+            MeshKind::Plane { normal, size } => Plane3d::new(normal, size).into(),
+            MeshKind::Circle { r } => Circle::new(r).into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct SceneObject {
+    mesh: MeshKind,
+    #[serde(default)]
+    transform: Transform,
+    #[serde(default = "SceneObject::default_color")]
+    color: Color,
+    // Whether the camera collides with the object.
+    #[serde(default)]
+    collider: bool,
+}
+
+impl SceneObject {
+    fn default_color() -> Color {
+        Color::WHITE
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+struct SceneDescription {
+    objects: Vec<SceneObject>,
+}
+
+impl SceneDescription {
+    fn save(&self, path: &Path) -> Result<(), BevyError> {
+        let ron = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, ron)?;
+        Ok(())
+    }
+
+    fn load(path: &Path) -> Result<Self, BevyError> {
+        let ron = std::fs::read_to_string(path)?;
+        Ok(ron::from_str(&ron)?)
+    }
+}
+
+// Hands out one handle per distinct mesh or material description, so identical scene objects
+// share their assets instead of each uploading a copy to the GPU.
+#[derive(Resource, Default)]
+struct AssetCache {
+    meshes: HashMap<u64, Handle<Mesh>>,
+    materials: HashMap<u64, Handle<StandardMaterial>>,
+    // What each cached mesh was made from, for writing it back out.
+    mesh_kinds: HashMap<AssetId<Mesh>, MeshKind>,
+}
+
+impl AssetCache {
+    fn mesh(&mut self, meshes: &mut Assets<Mesh>, kind: MeshKind) -> Handle<Mesh> {
+        let handle = self
+            .meshes
+            .entry(description_hash(&kind))
+            .or_insert_with(|| meshes.add(kind.mesh()))
+            .clone();
+        self.mesh_kinds.insert(handle.id(), kind);
+        handle
+    }
+
+    // The shape a mesh handed out by `mesh` was made from, `None` for any other mesh.
+    fn mesh_kind(&self, id: AssetId<Mesh>) -> Option<MeshKind> {
+        self.mesh_kinds.get(&id).copied()
+    }
+
+    fn material(
+        &mut self,
+        materials: &mut Assets<StandardMaterial>,
+        color: Color,
+    ) -> Handle<StandardMaterial> {
+        self.materials
+            .entry(description_hash(&color))
+            .or_insert_with(|| materials.add(color))
+            .clone()
+    }
+}
+
+// Hashes the serialized form, which sidesteps floats not implementing `Hash`.
+fn description_hash(description: &impl Serialize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    ron::to_string(description)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+fn load_startup_scene(mut startup_scene: ResMut<StartupScene>) {
+    let Some(path) = std::env::args_os().nth(1).map(PathBuf::from) else {
+        return;
+    };
+    match SceneDescription::load(&path) {
+        Ok(description) => startup_scene.0 = Some((path, description)),
+        Err(error) => error!(
+            "Could not load the scene from {}, showing the built-in world instead: {error}",
+            path.display()
+        ),
+    }
+}
+
+// Places the objects from the startup scene, or the extras from the scene description file
+// when starting with the built-in world. Objects are in meters like the rest of the scene.
+fn spawn_scene_description(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut asset_cache: ResMut<AssetCache>,
+    startup_scene: Res<StartupScene>,
+    world_scale: Res<WorldScale>,
+) {
+    let (path, description) = if let Some(scene) = startup_scene.0.clone() {
+        scene
+    } else {
+        let path = PathBuf::from(SCENE_DESCRIPTION_PATH);
+        if !path.exists() {
+            return;
+        }
+        match SceneDescription::load(&path) {
+            Ok(description) => (path, description),
+            Err(error) => {
+                warn!("Could not load the scene from {}: {error}", path.display());
+                return;
+            }
+        }
+    };
+
+    let root = commands
+        .spawn((
+            Transform::from_scale(Vec3::splat(world_scale.0)),
+            Visibility::default(),
+        ))
+        .id();
+    for object in &description.objects {
+        let mut entity = commands.spawn((
+            ChildOf(root),
+            WorldEntity,
+            SceneMaterial,
+            Mesh3d(asset_cache.mesh(&mut meshes, object.mesh)),
+            MeshMaterial3d(asset_cache.material(&mut materials, object.color)),
+            object.transform,
+        ));
+        if object.collider {
+            entity.insert(CameraCollider::default());
+        }
+    }
+    info!(
+        "Placed {} objects from {}",
+        description.objects.len(),
+        path.display()
+    );
+}
+
+// Writes the world entities back out as a scene description with Shift+Enter, to the file the
+// startup scene came from or else the scene description file, so the next launch places them
+// again. Objects whose mesh or material a scene description can't express are left out.
+#[allow(clippy::type_complexity)]
+fn export_scene_description(
+    startup_scene: Res<StartupScene>,
+    asset_cache: Res<AssetCache>,
+    materials: Res<Assets<StandardMaterial>>,
+    entity_query: Query<
+        (
+            Entity,
+            &Mesh3d,
+            &MeshMaterial3d<StandardMaterial>,
+            &GlobalTransform,
+            Has<CameraCollider>,
+        ),
+        With<WorldEntity>,
+    >,
+    world_scale: Res<WorldScale>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    let shift = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if !shift || !input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+
+    let mut description = SceneDescription::default();
+    for (entity, mesh, material, global_transform, collider) in &entity_query {
+        let Some(mesh) = asset_cache.mesh_kind(mesh.id()) else {
+            warn!("Skipped exporting {entity}, its mesh wasn't made from a scene description");
+            continue;
+        };
+        // Only flat colours can be described, textured materials are skipped
+        let color = materials
+            .get(material.id())
+            .filter(|material| material.base_color_texture.is_none())
+            .map(|material| material.base_color);
+        let Some(color) = color else {
+            warn!("Skipped exporting {entity}, its material isn't a plain colour");
+            continue;
+        };
+
+        // Scene descriptions are in meters, whether the entity sits under a scaled root or not
+        let mut transform = global_transform.compute_transform();
+        transform.translation /= world_scale.0;
+        transform.scale /= world_scale.0;
+        description.objects.push(SceneObject {
+            mesh,
+            transform,
+            color,
+            collider,
+        });
+    }
+
+    let path = startup_scene.0.as_ref().map_or_else(
+        || PathBuf::from(SCENE_DESCRIPTION_PATH),
+        |(path, _)| path.clone(),
+    );
+    match description.save(&path) {
+        Ok(()) => info!(
+            "Exported {} objects to {}",
+            description.objects.len(),
+            path.display()
+        ),
+        Err(error) => warn!("Could not export the scene to {}: {error}", path.display()),
+    }
+}
+
+// Directory the scene textures are loaded from, relative to the asset folder. Defaults to
+// `textures`, set the `TEXTURE_ROOT` environment variable to load them from somewhere else.
+#[derive(Resource)]
+struct TextureRoot(PathBuf);
+
+impl Default for TextureRoot {
+    fn default() -> Self {
+        Self(
+            std::env::var_os("TEXTURE_ROOT")
+                .map_or_else(|| PathBuf::from("textures"), PathBuf::from),
+        )
+    }
+}
+
+// Warns early when the texture directory is missing, rather than with one error per texture.
+fn check_texture_root(texture_root: Res<TextureRoot>) {
+    // Assumes the default `assets` folder of the asset plugin
+    let directory = FileAssetReader::get_base_path()
+        .join("assets")
+        .join(&texture_root.0);
+    if !directory.is_dir() {
+        warn!(
+            "Texture directory {} does not exist, textures will fail to load",
+            directory.display()
+        );
+    }
+}
+
+fn spawn_world(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    asset_server: Res<AssetServer>,
+    world_scale: Res<WorldScale>,
+    texture_root: Res<TextureRoot>,
+) {
+    build_demo_world(
+        &mut commands,
+        &mut materials,
+        &mut meshes,
+        &asset_server,
+        world_scale.0,
+        &texture_root.0,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn waypoints_round_trip_and_survive_a_broken_file() {
+        let dir = std::env::temp_dir().join(format!("waypoints-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("waypoints.ron");
+
+        let saved = Waypoints {
+            bookmarks: vec![Waypoint {
+                name: "Waypoint 1".to_string(),
+                transform: Transform::from_xyz(1.0, 2.0, 3.0),
+            }],
+            current: Some(0),
+        };
+        saved.save(&path).unwrap();
+
+        let mut waypoints = Waypoints::default();
+        waypoints.reload(&path).unwrap();
+        assert_eq!(waypoints.bookmarks.len(), 1);
+        assert_eq!(waypoints.bookmarks[0].name, "Waypoint 1");
+        assert_eq!(
+            waypoints.bookmarks[0].transform,
+            Transform::from_xyz(1.0, 2.0, 3.0)
+        );
+        assert_eq!(waypoints.current, None);
+
+        std::fs::write(&path, "(bookmarks: [(name: ").unwrap();
+        assert!(waypoints.reload(&path).is_err());
+        assert!(waypoints.reload(&dir.join("missing.ron")).is_err());
+        assert_eq!(waypoints.bookmarks.len(), 1);
+        assert_eq!(waypoints.bookmarks[0].name, "Waypoint 1");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn scene_objects_deserialize_every_mesh_kind() {
+        let scene: SceneDescription = ron::from_str(
+            "(objects: [
+                (mesh: Cuboid(x: 1.0, y: 2.0, z: 3.0), collider: true),
+                (mesh: Sphere(r: 0.5)),
+                (mesh: Cylinder(r: 0.5, h: 2.0)),
+                (mesh: Plane(normal: (0.0, 1.0, 0.0), size: (10.0, 10.0))),
+                (mesh: Circle(r: 4.0)),
+            ])",
+        )
+        .unwrap();
+
+        let meshes: Vec<MeshKind> = scene.objects.iter().map(|object| object.mesh).collect();
+        assert_eq!(
+            meshes,
+            [
+                MeshKind::Cuboid {
+                    x: 1.0,
+                    y: 2.0,
+                    z: 3.0
+                },
+                MeshKind::Sphere { r: 0.5 },
+                MeshKind::Cylinder { r: 0.5, h: 2.0 },
+                MeshKind::Plane {
+                    normal: Vec3::Y,
+                    size: Vec2::splat(10.0)
+                },
+                MeshKind::Circle { r: 4.0 },
+            ]
+        );
+        assert!(scene.objects[0].collider);
+        assert!(!scene.objects[1].collider);
+        assert_eq!(scene.objects[1].color, Color::WHITE);
+        assert_eq!(scene.objects[1].transform, Transform::IDENTITY);
+    }
+
+    #[test]
+    fn asset_cache_shares_handles_between_identical_descriptions() {
+        let mut meshes = Assets::<Mesh>::default();
+        let mut materials = Assets::<StandardMaterial>::default();
+        let mut cache = AssetCache::default();
+
+        let sphere = MeshKind::Sphere { r: 0.5 };
+        let first = cache.mesh(&mut meshes, sphere);
+        let second = cache.mesh(&mut meshes, sphere);
+        let other = cache.mesh(&mut meshes, MeshKind::Sphere { r: 1.0 });
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert_eq!(meshes.len(), 2);
+        assert_eq!(cache.mesh_kind(first.id()), Some(sphere));
+
+        let red = cache.material(&mut materials, Color::srgb(1.0, 0.0, 0.0));
+        let also_red = cache.material(&mut materials, Color::srgb(1.0, 0.0, 0.0));
+        let blue = cache.material(&mut materials, Color::srgb(0.0, 0.0, 1.0));
+        assert_eq!(red, also_red);
+        assert_ne!(red, blue);
+        assert_eq!(materials.len(), 2);
+    }
+
+    #[test]
+    fn sensitivity_steps() {
+        let additive = SensitivitySteps::Additive;
+        assert!((additive.step(0.2, true) - 0.205).abs() < 1e-6);
+        assert!((additive.step(0.2, false) - 0.195).abs() < 1e-6);
+
+        let multiplicative = SensitivitySteps::Multiplicative;
+        assert!((multiplicative.step(0.2, true) - 0.205).abs() < 1e-6);
+        assert!((multiplicative.step(2.0, true) - 2.05).abs() < 1e-5);
+        let down = multiplicative.step(2.0, false);
+        assert!((multiplicative.step(down, true) - 2.0).abs() < 1e-5);
+
+        // Neither steps below the smallest sensitivity
+        assert_eq!(additive.step(SENSITIVITY_STEP, false), SENSITIVITY_STEP);
+        assert_eq!(
+            multiplicative.step(SENSITIVITY_STEP, false),
+            SENSITIVITY_STEP
+        );
+    }
+
+    #[test]
+    fn telemetry_path_comes_from_the_config() {
+        let mut world = World::new();
+        assert_eq!(
+            TelemetryLogger::from_world(&mut world).path,
+            Path::new(TELEMETRY_PATH)
+        );
+
+        let config: Config = ron::from_str("(telemetry_path: Some(\"logs/run.csv\"))").unwrap();
+        world.insert_resource(config);
+        assert_eq!(
+            TelemetryLogger::from_world(&mut world).path,
+            Path::new("logs/run.csv")
+        );
+    }
+
+    #[test]
+    fn photo_mode_stops_the_cursor_grab_until_it_ends() {
+        let mut world = World::new();
+        world.init_resource::<Time<Virtual>>();
+        world.init_resource::<PhotoModeSettings>();
+        let camera = world.spawn(FreeCamera::default()).id();
+        let window = world
+            .spawn(CursorOptions {
+                grab_mode: CursorGrabMode::Locked,
+                visible: false,
+                ..default()
+            })
+            .id();
+
+        world.run_system_once(enter_photo_mode).unwrap();
+        assert!(!world.get::<FreeCamera>(camera).unwrap().cursor_grab_enabled);
+        let cursor = world.get::<CursorOptions>(window).unwrap();
+        assert_eq!(cursor.grab_mode, CursorGrabMode::None);
+        assert!(cursor.visible);
+
+        world.run_system_once(exit_photo_mode).unwrap();
+        assert!(world.get::<FreeCamera>(camera).unwrap().cursor_grab_enabled);
+        let cursor = world.get::<CursorOptions>(window).unwrap();
+        assert_eq!(cursor.grab_mode, CursorGrabMode::Locked);
+        assert!(!cursor.visible);
+        assert!(!world.contains_resource::<PhotoModeSnapshot>());
+    }
+
+    #[test]
+    fn render_scale_text_only_shows_with_the_image_target() {
+        for (target, texts) in [
+            (RenderTargetConfig::Window, 0),
+            (RenderTargetConfig::Image, 1),
+        ] {
+            let mut app = App::new();
+            app.add_plugins((MinimalPlugins, RenderScalePlugin))
+                .insert_resource(Config {
+                    render_target: target,
+                    ..default()
+                })
+                .add_systems(PreStartup, |mut commands: Commands| {
+                    commands.spawn(InfoPanel);
+                });
+            // Skips `Startup`, the scene view it spawns needs a window
+            let world = app.world_mut();
+            world.run_schedule(PreStartup);
+            world.run_schedule(PostStartup);
+
+            let mut query = world.query_filtered::<(), With<RenderScaleText>>();
+            assert_eq!(query.iter(world).count(), texts, "{target:?}");
+        }
+    }
+
+    #[test]
+    fn wall_placement_status_reads_on_or_off() {
+        let mut placement = WallPlacement::default();
+        assert_eq!(placement.status(), "Wall placement: off");
+
+        placement.active = true;
+        placement.rotated = true;
+        assert_eq!(
+            placement.status(),
+            "Wall placement: on (3.00 m high, along Z, 0 placed)"
+        );
+    }
+}